@@ -0,0 +1,114 @@
+//! Benchmarks of the propagation, the conflict analysis and whole solves on
+//! generated instances, small enough to run in CI.
+//!
+//! Run by `cargo bench`.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use yasuosat::{LitInt, SolveResult, Solver};
+
+/// Random 3-SAT with `n` variables and `ratio * n` clauses, which is hardest
+/// near the phase transition at the ratio `4.26`. The same seed gives the
+/// same formula.
+fn random_3sat(n: usize, ratio: f64, mut seed: u64) -> Vec<Vec<LitInt>> {
+	let mut next = |m: u64| {
+		seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+		(seed >> 33) % m
+	};
+	(0..(ratio * n as f64) as usize)
+		.map(|_| {
+			let mut clause: Vec<LitInt> = Vec::new();
+			while clause.len() < 3 {
+				let var = 1 + next(n as u64) as LitInt;
+				if !clause.iter().any(|l| l.abs() == var) {
+					clause.push(if next(2) == 0 { var } else { -var });
+				}
+			}
+			clause
+		})
+		.collect()
+}
+
+/// `p` pigeons in `h` holes, each in at most one, which is unsatisfiable if
+/// `p > h` and needs exponentially many conflicts.
+fn pigeonhole(p: usize, h: usize) -> (usize, Vec<Vec<LitInt>>) {
+	let var = |i: usize, j: usize| (i * h + j + 1) as LitInt;
+	let mut clauses: Vec<Vec<LitInt>> = (0..p)
+		.map(|i| (0..h).map(|j| var(i, j)).collect())
+		.collect();
+	for j in 0..h {
+		for i in 0..p {
+			for k in i + 1..p {
+				clauses.push(vec![-var(i, j), -var(k, j)]);
+			}
+		}
+	}
+	(p * h, clauses)
+}
+
+/// The implications `x_i -> x_{i+1}` with a ternary clause on each step, so
+/// that assuming `1` assigns every variable by propagation alone.
+fn chain(n: usize) -> Vec<Vec<LitInt>> {
+	(1..n as LitInt)
+		.flat_map(|i| vec![vec![-i, i + 1], vec![-i, i + 1, -(i % 7 + 1)]])
+		.collect()
+}
+
+fn propagation(c: &mut Criterion) {
+	let mut group = c.benchmark_group("propagation");
+	for &n in &[1_000, 100_000] {
+		let clauses = chain(n);
+		group.throughput(Throughput::Elements(n as u64));
+		group.bench_with_input(BenchmarkId::new("chain", n), &clauses, |b, clauses| {
+			let mut ss = Solver::new(n, clauses).unwrap();
+			b.iter(|| assert!(ss.solve_with_assumptions(&[1]).is_sat()));
+		});
+	}
+	group.finish();
+}
+
+fn analysis(c: &mut Criterion) {
+	// Each iteration is a fresh solve of the same number of conflicts.
+	const CONFLICTS: u64 = 2_000;
+	let mut group = c.benchmark_group("analysis");
+	group.throughput(Throughput::Elements(CONFLICTS));
+	let (n, clauses) = pigeonhole(9, 8);
+	group.bench_function("pigeonhole 9 8", |b| {
+		b.iter_batched(
+			|| {
+				let mut ss = Solver::new(n, &clauses).unwrap();
+				ss.set_conflict_budget(Some(CONFLICTS));
+				ss
+			},
+			|mut ss| assert_eq!(ss.solve(), SolveResult::Unknown),
+			BatchSize::LargeInput,
+		);
+	});
+	group.finish();
+}
+
+fn solve(c: &mut Criterion) {
+	let mut group = c.benchmark_group("solve");
+	group.sample_size(20);
+	for &n in &[50, 100] {
+		let instances: Vec<_> = (0..10).map(|seed| random_3sat(n, 4.26, seed)).collect();
+		group.bench_with_input(
+			BenchmarkId::new("random 3-SAT x10", n),
+			&instances,
+			|b, instances| {
+				b.iter(|| {
+					for clauses in instances {
+						Solver::new(n, clauses).unwrap().solve();
+					}
+				});
+			},
+		);
+	}
+	let (n, clauses) = pigeonhole(7, 6);
+	group.bench_function("pigeonhole 7 6", |b| {
+		b.iter(|| assert!(Solver::new(n, &clauses).unwrap().solve().is_unsat()));
+	});
+	group.finish();
+}
+
+criterion_group!(benches, propagation, analysis, solve);
+criterion_main!(benches);
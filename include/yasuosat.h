@@ -0,0 +1,26 @@
+/* C API of yasuosat, built with the `ffi` feature. */
+#ifndef YASUOSAT_H
+#define YASUOSAT_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+typedef struct Solver Solver;
+
+Solver *yasuosat_new(size_t n);
+/* Returns 0 on success, -1 on an invalid clause. */
+int yasuosat_add_clause(Solver *solver, const int *lits, size_t len);
+/* Returns 10 (SAT), 20 (UNSAT) or 0 (unknown). */
+int yasuosat_solve(Solver *solver);
+/* Returns var or -var by the model, 0 if there is no model. */
+int yasuosat_value(const Solver *solver, int var);
+void yasuosat_free(Solver *solver);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
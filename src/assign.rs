@@ -0,0 +1,161 @@
+//! The values of the variables, one byte for each, or two bits for each with
+//! the `packed-assignment` feature.
+
+use std::ops::Index;
+
+/// The value of each variable, indexed by the variable. The index 0 is
+/// unused.
+#[cfg(not(feature = "packed-assignment"))]
+#[derive(Clone)]
+pub(crate) struct Assignment(Vec<Option<bool>>);
+
+/// The value of each variable, indexed by the variable. The index 0 is
+/// unused.
+///
+/// Each word holds 32 variables, with the bit `2 * i` set if the variable
+/// `i` (modulo 32) is assigned, and the bit `2 * i + 1` set if it is true.
+#[cfg(feature = "packed-assignment")]
+#[derive(Clone)]
+pub(crate) struct Assignment {
+	words: Vec<u64>,
+	len: usize,
+}
+
+#[cfg(not(feature = "packed-assignment"))]
+impl Assignment {
+	/// Creates the assignment of the variables `1..=n`, all unassigned.
+	pub(crate) fn new(n: usize) -> Self {
+		Assignment(vec![None; n + 1])
+	}
+
+	/// Adds the variable `n + 1`, which is unassigned.
+	pub(crate) fn grow(&mut self) {
+		self.0.push(None);
+	}
+
+	/// # Safety
+	///
+	/// `var` must be at most `n`.
+	#[inline]
+	pub(crate) unsafe fn get_unchecked(&self, var: usize) -> Option<bool> {
+		*self.0.get_unchecked(var)
+	}
+
+	#[inline]
+	pub(crate) fn set(&mut self, var: usize, value: Option<bool>) {
+		self.0[var] = value;
+	}
+
+	/// The values from the index 0.
+	pub(crate) fn iter(&self) -> impl Iterator<Item = Option<bool>> + '_ {
+		self.0.iter().copied()
+	}
+}
+
+#[cfg(feature = "packed-assignment")]
+impl Assignment {
+	/// Creates the assignment of the variables `1..=n`, all unassigned.
+	pub(crate) fn new(n: usize) -> Self {
+		Assignment {
+			words: vec![0; n / 32 + 1],
+			len: n + 1,
+		}
+	}
+
+	/// Adds the variable `n + 1`, which is unassigned.
+	pub(crate) fn grow(&mut self) {
+		if self.len / 32 == self.words.len() {
+			self.words.push(0);
+		}
+		self.len += 1;
+	}
+
+	/// # Safety
+	///
+	/// `var` must be at most `n`.
+	#[inline]
+	pub(crate) unsafe fn get_unchecked(&self, var: usize) -> Option<bool> {
+		let bits = *self.words.get_unchecked(var / 32) >> (2 * (var % 32));
+		if bits & 1 == 0 {
+			None
+		} else {
+			Some(bits & 2 != 0)
+		}
+	}
+
+	#[inline]
+	pub(crate) fn set(&mut self, var: usize, value: Option<bool>) {
+		debug_assert!(var < self.len, "bad variable {}", var);
+		let bits = match value {
+			None => 0,
+			Some(false) => 1,
+			Some(true) => 3,
+		};
+		let word = &mut self.words[var / 32];
+		*word = *word & !(3 << (2 * (var % 32))) | bits << (2 * (var % 32));
+	}
+
+	/// The values from the index 0.
+	pub(crate) fn iter(&self) -> impl Iterator<Item = Option<bool>> + '_ {
+		(0..self.len).map(move |var| unsafe { self.get_unchecked(var) })
+	}
+}
+
+#[cfg(not(feature = "packed-assignment"))]
+impl Index<usize> for Assignment {
+	type Output = Option<bool>;
+
+	#[inline]
+	fn index(&self, var: usize) -> &Option<bool> {
+		&self.0[var]
+	}
+}
+
+#[cfg(feature = "packed-assignment")]
+impl Index<usize> for Assignment {
+	type Output = Option<bool>;
+
+	/// Returns one of the constants, since the bits cannot be referred to.
+	#[inline]
+	fn index(&self, var: usize) -> &Option<bool> {
+		assert!(var < self.len, "bad variable {}", var);
+		match unsafe { self.get_unchecked(var) } {
+			None => &None,
+			Some(false) => &Some(false),
+			Some(true) => &Some(true),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn assignment_test() {
+		let mut assign = Assignment::new(40);
+		assert!(assign.iter().all(|v| v.is_none()));
+		for var in 1..=40 {
+			assign.set(var, [None, Some(false), Some(true)][var % 3]);
+		}
+		assign.set(33, Some(true));
+		assign.set(33, Some(false));
+		assign.set(34, None);
+		for _ in 0..30 {
+			assign.grow();
+		}
+		assign.set(70, Some(true));
+		for var in 1..=70 {
+			let expected = match var {
+				33 => Some(false),
+				34 => None,
+				70 => Some(true),
+				41..=69 => None,
+				_ => [None, Some(false), Some(true)][var % 3],
+			};
+			assert_eq!(assign[var], expected, "{}", var);
+			assert_eq!(unsafe { assign.get_unchecked(var) }, expected);
+		}
+		assert_eq!(assign.iter().count(), 71);
+	}
+}
@@ -0,0 +1,278 @@
+//! Reading and writing formulas in a compact binary format.
+//!
+//! The input starts with the four bytes `ycnf` and the version byte `1`,
+//! followed by unsigned integers in the LEB128 encoding:
+//!
+//! 1. the number of variables `n`,
+//! 2. the number of clauses `m`,
+//! 3. `m` clauses, each the number of its literals followed by the literals.
+//!
+//! The literal `x` is written as `2 * x`, and `-x` as `2 * x + 1`, as in the
+//! binary DRAT format. An integer is written 7 bits at a time from the
+//! lowest ones, each in a byte whose highest bit is set unless it is the
+//! last byte, so it takes at most 10 bytes for 64 bits. The input ends after
+//! the last clause.
+//!
+//! For example, `p cnf 3 2`, `1 -3 0` and `0` are the bytes
+//! `79 63 6e 66 01 03 02 02 02 07 00`.
+
+use crate::{LitFunctions, LitInt, Solver};
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+
+const MAGIC: &[u8; 5] = b"ycnf\x01";
+
+/// Errors in the binary input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BinaryError {
+	/// The input does not start with `ycnf` and the version, or the number
+	/// of variables or clauses does not fit in `usize`.
+	BadHeader,
+	/// An integer is longer than 64 bits.
+	BadInteger,
+	/// The clause at the index (0-based) has the literal of the variable `0`
+	/// or out of `1..=n`.
+	BadLiteral { clause: usize },
+	/// The input continues after the last clause.
+	TrailingData,
+	/// Reading the input failed, including an input which ends early.
+	Io {
+		kind: io::ErrorKind,
+		message: String,
+	},
+}
+
+impl fmt::Display for BinaryError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			BinaryError::BadHeader => write!(f, "bad header"),
+			BinaryError::BadInteger => write!(f, "integer longer than 64 bits"),
+			BinaryError::BadLiteral { clause } => write!(f, "clause {}: bad literal", clause),
+			BinaryError::TrailingData => write!(f, "data after the last clause"),
+			BinaryError::Io { message, .. } => write!(f, "{}", message),
+		}
+	}
+}
+
+impl std::error::Error for BinaryError {}
+
+impl From<io::Error> for BinaryError {
+	fn from(error: io::Error) -> Self {
+		BinaryError::Io {
+			kind: error.kind(),
+			message: error.to_string(),
+		}
+	}
+}
+
+fn write_integer<W: Write>(writer: &mut W, mut value: u64) -> io::Result<()> {
+	while value >= 0x80 {
+		writer.write_all(&[value as u8 | 0x80])?;
+		value >>= 7;
+	}
+	writer.write_all(&[value as u8])
+}
+
+fn read_integer<R: BufRead>(reader: &mut R) -> Result<u64, BinaryError> {
+	let mut value = 0;
+	for shift in (0..64).step_by(7) {
+		let mut byte = [0];
+		reader.read_exact(&mut byte)?;
+		let bits = u64::from(byte[0] & 0x7f);
+		// Only the lowest bit is left for the 10th byte.
+		if shift == 63 && bits > 1 {
+			return Err(BinaryError::BadInteger);
+		}
+		value |= bits << shift;
+		if byte[0] & 0x80 == 0 {
+			return Ok(value);
+		}
+	}
+	Err(BinaryError::BadInteger)
+}
+
+fn read_size<R: BufRead>(reader: &mut R) -> Result<usize, BinaryError> {
+	let value = read_integer(reader)?;
+	usize::try_from(value).map_err(|_| BinaryError::BadHeader)
+}
+
+/// Reads the clause at the index `i`, whose literals are checked later by
+/// `from_clauses` but for the ones too large for [`LitInt`].
+fn read_clause<R: BufRead>(reader: &mut R, i: usize) -> Result<Vec<LitInt>, BinaryError> {
+	let len = read_integer(reader)?;
+	// The length is not trusted for the allocation.
+	let mut clause = Vec::with_capacity(len.min(1 << 10) as usize);
+	for _ in 0..len {
+		let code = read_integer(reader)?;
+		let var = LitInt::try_from(code / 2).map_err(|_| BinaryError::BadLiteral { clause: i })?;
+		clause.push(if code & 1 == 1 { -var } else { var });
+	}
+	Ok(clause)
+}
+
+impl Solver {
+	/// Writes the original clauses like [`Solver::to_clauses`] in the binary
+	/// format described in [`Solver::read_binary`].
+	pub fn write_binary<W: Write>(&self, writer: W) -> io::Result<()> {
+		let mut writer = BufWriter::new(writer);
+		writer.write_all(MAGIC)?;
+		write_integer(&mut writer, self.n as u64)?;
+		write_integer(
+			&mut writer,
+			(self.num_original() + self.empty as usize) as u64,
+		)?;
+		for clause in self.original_clauses() {
+			write_integer(&mut writer, clause.len() as u64)?;
+			for lit in clause {
+				write_integer(&mut writer, lit.get_loc() as u64)?;
+			}
+		}
+		if self.empty {
+			write_integer(&mut writer, 0)?;
+		}
+		writer.flush()
+	}
+
+	/// Creates a solver from the input in the binary format written by
+	/// [`Solver::write_binary`]:
+	///
+	/// - the bytes `ycnf` and the version byte `1`,
+	/// - the number of variables `n` and the number of clauses `m`,
+	/// - `m` clauses, each the number of its literals followed by the
+	///   literals, where `x` is `2 * x` and `-x` is `2 * x + 1`,
+	/// - the end of the input,
+	///
+	/// where the numbers are unsigned LEB128 integers, 7 bits in each byte
+	/// from the lowest ones, with the highest bit set on all the bytes but
+	/// the last one.
+	///
+	/// The clauses are checked and packed as they are read, like
+	/// [`Solver::from_clauses`], so a repeated literal is kept once and a
+	/// tautology is dropped.
+	pub fn read_binary<R: Read>(reader: R) -> Result<Solver, BinaryError> {
+		let mut reader = BufReader::new(reader);
+		let mut magic = [0; 5];
+		match reader.read_exact(&mut magic) {
+			Ok(()) if &magic == MAGIC => {}
+			Err(error) if error.kind() != io::ErrorKind::UnexpectedEof => return Err(error.into()),
+			_ => return Err(BinaryError::BadHeader),
+		}
+		let n = read_size(&mut reader)?;
+		let m = read_size(&mut reader)?;
+		let mut error = None;
+		let clauses = (0..m).map_while(|i| match read_clause(&mut reader, i) {
+			Ok(clause) => Some(clause),
+			Err(e) => {
+				error = Some(e);
+				None
+			}
+		});
+		let solver = Solver::from_clauses(n, clauses);
+		if let Some(error) = error {
+			return Err(error);
+		}
+		let solver = solver.map_err(|error| BinaryError::BadLiteral {
+			clause: error.clause,
+		})?;
+		if !reader.fill_buf()?.is_empty() {
+			return Err(BinaryError::TrailingData);
+		}
+		Ok(solver)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::{lcg, random_cnf};
+
+	fn binary(ss: &Solver) -> Vec<u8> {
+		let mut out = Vec::new();
+		ss.write_binary(&mut out).unwrap();
+		out
+	}
+
+	#[test]
+	fn integer_test() {
+		for &value in &[0, 1, 127, 128, 300, 1 << 35, u64::MAX - 1, u64::MAX] {
+			let mut out = Vec::new();
+			write_integer(&mut out, value).unwrap();
+			assert_eq!(out.len(), 1.max((70 - value.leading_zeros() as usize) / 7));
+			assert_eq!(read_integer(&mut &out[..]), Ok(value));
+		}
+		let mut out = Vec::new();
+		write_integer(&mut out, 300).unwrap();
+		assert_eq!(out, [0xac, 0x02]);
+		let mut long = vec![0xff; 9];
+		long.push(0x02);
+		assert_eq!(read_integer(&mut &long[..]), Err(BinaryError::BadInteger));
+		long.insert(0, 0xff);
+		assert_eq!(read_integer(&mut &long[..]), Err(BinaryError::BadInteger));
+	}
+
+	#[test]
+	fn read_binary_test() {
+		let ss = Solver::new(3, &[vec![1, -3], vec![]]).unwrap();
+		let bytes = b"ycnf\x01\x03\x02\x02\x02\x07\x00";
+		assert_eq!(binary(&ss), bytes);
+		let read = Solver::read_binary(&bytes[..]).unwrap();
+		assert_eq!(read.to_string(), ss.to_string());
+
+		// 300 variables take two bytes each.
+		let ss = Solver::new(300, &[vec![-300, 1, 2], vec![3, 3], vec![4, -4]]).unwrap();
+		let bytes = binary(&ss);
+		assert_eq!(bytes[5..8], [0xac, 0x02, 0x02]);
+		assert_eq!(bytes[8..13], [0x03, 0xd9, 0x04, 0x02, 0x04]);
+		assert_eq!(
+			Solver::read_binary(&bytes[..]).unwrap().to_clauses(),
+			vec![vec![-300, 1, 2], vec![3]]
+		);
+	}
+
+	#[test]
+	fn read_binary_error_test() {
+		let read = |bytes: &[u8]| Solver::read_binary(bytes).map(|ss| ss.to_string());
+		assert_eq!(read(b""), Err(BinaryError::BadHeader));
+		assert_eq!(read(b"ycnf\x02\x00\x00"), Err(BinaryError::BadHeader));
+		assert_eq!(read(b"ycnf\x01\x00\x00"), Ok("p cnf 0 0\n".to_owned()));
+		assert_eq!(
+			read(b"ycnf\x01\x00\x00\x00"),
+			Err(BinaryError::TrailingData)
+		);
+		// The literals 0 and 4 with 1 variable.
+		assert_eq!(
+			read(b"ycnf\x01\x01\x02\x01\x02\x01\x00"),
+			Err(BinaryError::BadLiteral { clause: 1 })
+		);
+		assert_eq!(
+			read(b"ycnf\x01\x01\x01\x02\x02\x04"),
+			Err(BinaryError::BadLiteral { clause: 0 })
+		);
+		match read(b"ycnf\x01\x02\x02\x02\x02\x04\x03") {
+			Err(BinaryError::Io { kind, .. }) => assert_eq!(kind, io::ErrorKind::UnexpectedEof),
+			result => panic!("{:?}", result),
+		}
+		// A clause of `u64::MAX` literals.
+		let mut bytes = b"ycnf\x01\x01\x01".to_vec();
+		bytes.extend_from_slice(&[0xff; 9]);
+		bytes.push(0x01);
+		bytes.push(0x02);
+		assert!(matches!(read(&bytes), Err(BinaryError::Io { .. })));
+	}
+
+	#[test]
+	fn round_trip_test() {
+		let mut next = lcg(5);
+		for _ in 0..20 {
+			let n = 1 + next(1000) as usize;
+			let clauses = random_cnf(next(1 << 32), n, next(200) as usize, false);
+			let ss = Solver::new(n, &clauses).unwrap();
+			let bytes = binary(&ss);
+			assert!(bytes.len() <= ss.to_string().len());
+			let read = Solver::read_binary(&bytes[..]).unwrap();
+			assert_eq!(read.to_clauses(), ss.to_clauses());
+			assert_eq!(binary(&read), bytes);
+		}
+	}
+}
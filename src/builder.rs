@@ -0,0 +1,99 @@
+//! Building a solver whose number of variables is not known in advance.
+
+use crate::{normalize_clause, ClauseError, LitInt, Solver};
+
+/// Accumulates clauses and creates a [`Solver`] over all the variables seen.
+///
+/// ```
+/// use yasuosat::SolverBuilder;
+///
+/// let mut builder = SolverBuilder::new();
+/// let (x, y) = (builder.new_var(), builder.new_var());
+/// builder.add_clause(&[x, y]).unwrap();
+/// builder.add_clause(&[-x]).unwrap();
+/// let mut solver = builder.build();
+/// assert!(solver.solve().is_sat());
+/// assert_eq!(solver.model(), Some(vec![false, true]));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SolverBuilder {
+	/// The largest variable allocated or used in the clauses.
+	n: usize,
+	clauses: Vec<Vec<LitInt>>,
+}
+
+impl SolverBuilder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Allocates a variable which is larger than any variable seen so far,
+	/// and returns its positive literal.
+	pub fn new_var(&mut self) -> LitInt {
+		assert!(self.n < LitInt::MAX as usize, "too many variables");
+		self.n += 1;
+		self.n as LitInt
+	}
+
+	/// The number of variables the solver will have.
+	pub fn num_vars(&self) -> usize {
+		self.n
+	}
+
+	/// Adds the clause. The variables in it need not be allocated by
+	/// [`SolverBuilder::new_var`]. A tautology is ignored, and a repeated
+	/// literal is kept once.
+	pub fn add_clause(&mut self, clause: &[LitInt]) -> Result<(), ClauseError> {
+		let normalized = normalize_clause(LitInt::MAX as usize, clause)?;
+		self.n = self.n.max(max_var(clause));
+		if let Some(clause) = normalized {
+			self.clauses.push(clause.into_owned());
+		}
+		Ok(())
+	}
+
+	/// Creates the solver with the clauses added so far.
+	pub fn build(self) -> Solver {
+		let clauses: Vec<_> = self.clauses.iter().map(|clause| &clause[..]).collect();
+		// The clauses are checked in `add_clause`.
+		unsafe { Solver::build(self.n, &clauses) }
+	}
+}
+
+fn max_var(clause: &[LitInt]) -> usize {
+	clause
+		.iter()
+		.map(|lit| lit.unsigned_abs() as usize)
+		.max()
+		.unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn builder_test() {
+		let mut builder = SolverBuilder::new();
+		let x = builder.new_var();
+		assert_eq!(x, 1);
+		builder.add_clause(&[x, -5]).unwrap();
+		assert_eq!(builder.new_var(), 6);
+		builder.add_clause(&[7, -7]).unwrap();
+		assert_eq!(builder.num_vars(), 7);
+		assert_eq!(builder.add_clause(&[2, 0]), Err(ClauseError::ZeroLiteral));
+		builder.add_clause(&[3, 3]).unwrap();
+		builder.add_clause(&[-x]).unwrap();
+		let mut ss = builder.build();
+		assert_eq!(ss.num_vars(), 7);
+		assert_eq!(ss.num_clauses(), 3);
+		assert_eq!(ss.to_clauses()[1], vec![3]);
+		assert!(ss.solve().is_sat());
+		let model = ss.model().unwrap();
+		assert!(!model[0] && !model[4]);
+
+		let mut ss = SolverBuilder::new().build();
+		assert_eq!(ss.num_vars(), 0);
+		assert!(ss.solve().is_sat());
+	}
+}
@@ -0,0 +1,465 @@
+//! Reading formulas in the DIMACS CNF format.
+
+use crate::{LitInt, Solver};
+#[cfg(feature = "gzip")]
+use flate2::read::GzDecoder;
+use std::collections::HashMap;
+use std::fmt;
+#[cfg(feature = "gzip")]
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+#[cfg(feature = "gzip")]
+use std::path::Path;
+
+/// Errors in the DIMACS input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DimacsError {
+	/// The `p cnf <vars> <clauses>` line is missing or malformed.
+	MissingHeader,
+	/// A token which is not an integer literal in `-vars..=vars`.
+	BadLiteral { line: usize, token: String },
+	/// The number of clauses differs from the one in the header.
+	CountMismatch { expected: usize, found: usize },
+	/// The clause at the index (0-based, in the input order) is rejected by
+	/// [`Solver::try_new`]. A literal repeated in a clause is kept once, so
+	/// this is not returned for the literals checked by the parser.
+	InvalidClause(usize),
+	/// Reading the input failed, including an input which is not UTF-8.
+	Io {
+		kind: io::ErrorKind,
+		message: String,
+	},
+}
+
+impl fmt::Display for DimacsError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			DimacsError::MissingHeader => write!(f, "missing `p cnf` header"),
+			DimacsError::BadLiteral { line, token } => {
+				write!(f, "line {}: bad literal `{}`", line, token)
+			}
+			DimacsError::CountMismatch { expected, found } => {
+				write!(f, "expected {} clauses but found {}", expected, found)
+			}
+			DimacsError::InvalidClause(i) => write!(f, "clause {} is invalid", i),
+			DimacsError::Io { message, .. } => write!(f, "{}", message),
+		}
+	}
+}
+
+impl std::error::Error for DimacsError {}
+
+impl From<io::Error> for DimacsError {
+	fn from(error: io::Error) -> Self {
+		DimacsError::Io {
+			kind: error.kind(),
+			message: error.to_string(),
+		}
+	}
+}
+
+/// The state of parsing the DIMACS CNF lines, shared by the string and the
+/// reader inputs.
+///
+/// Each clause is terminated by `0`, not by a newline, so that a clause can
+/// span multiple lines. The last clause may omit the terminating `0`.
+#[derive(Default)]
+struct Parser {
+	/// The number of variables and clauses in the header.
+	header: Option<(usize, usize)>,
+	clauses: Vec<Vec<LitInt>>,
+	clause: Vec<LitInt>,
+	/// Set at the `%` line, after which everything is ignored.
+	done: bool,
+	/// The names in the `c var N = NAME` comments, collected if it is given.
+	names: Option<HashMap<usize, String>>,
+}
+
+impl Parser {
+	/// Parses the line `i` (0-based).
+	fn line(&mut self, i: usize, line: &str) -> Result<(), DimacsError> {
+		let line = line.trim();
+		if self.done || line.is_empty() {
+			return Ok(());
+		}
+		if let Some(comment) = line.strip_prefix('c') {
+			if let (Some(names), Some((var, name))) = (&mut self.names, var_name(comment)) {
+				names.insert(var, name.to_owned());
+			}
+			return Ok(());
+		}
+		// Some benchmark files end with `%` followed by garbage.
+		if line.starts_with('%') {
+			self.done = true;
+			return Ok(());
+		}
+		if line.starts_with('p') {
+			if self.header.is_some() {
+				return Err(DimacsError::MissingHeader);
+			}
+			let tokens: Vec<_> = line.split_whitespace().collect();
+			self.header = match tokens[..] {
+				["p", "cnf", vars, count] => match (vars.parse(), count.parse()) {
+					(Ok(vars), Ok(count)) => Some((vars, count)),
+					_ => return Err(DimacsError::MissingHeader),
+				},
+				_ => return Err(DimacsError::MissingHeader),
+			};
+			return Ok(());
+		}
+		let (vars, _) = self.header.ok_or(DimacsError::MissingHeader)?;
+		for token in line.split_whitespace() {
+			let bad = || DimacsError::BadLiteral {
+				line: i + 1,
+				token: token.to_owned(),
+			};
+			let lit: LitInt = token.parse().map_err(|_| bad())?;
+			if lit == 0 {
+				self.clauses.push(std::mem::take(&mut self.clause));
+			} else if lit.unsigned_abs() as usize > vars {
+				return Err(bad());
+			} else {
+				self.clause.push(lit);
+			}
+		}
+		Ok(())
+	}
+
+	fn finish(mut self) -> Result<(usize, Vec<Vec<LitInt>>), DimacsError> {
+		let (vars, count) = self.header.ok_or(DimacsError::MissingHeader)?;
+		if !self.clause.is_empty() {
+			self.clauses.push(self.clause);
+		}
+		if self.clauses.len() != count {
+			return Err(DimacsError::CountMismatch {
+				expected: count,
+				found: self.clauses.len(),
+			});
+		}
+		Ok((vars, self.clauses))
+	}
+}
+
+/// The variable and the name of the comment `var N = NAME`, following `c`.
+fn var_name(comment: &str) -> Option<(usize, &str)> {
+	let (var, name) = comment.trim_start().strip_prefix("var")?.split_once('=')?;
+	let var = var.trim().parse().ok()?;
+	let name = name.trim();
+	if var == 0 || name.is_empty() {
+		return None;
+	}
+	Some((var, name))
+}
+
+/// Parses the DIMACS CNF text into the number of variables and the clauses.
+pub(crate) fn parse(input: &str) -> Result<(usize, Vec<Vec<LitInt>>), DimacsError> {
+	let mut parser = Parser::default();
+	for (i, line) in input.lines().enumerate() {
+		parser.line(i, line)?;
+	}
+	parser.finish()
+}
+
+/// Parses the DIMACS CNF input line by line, without reading it at once.
+fn parse_reader<R: BufRead>(mut reader: R) -> Result<(usize, Vec<Vec<LitInt>>), DimacsError> {
+	let mut parser = Parser::default();
+	let mut line = String::new();
+	for i in 0.. {
+		line.clear();
+		if reader.read_line(&mut line)? == 0 {
+			break;
+		}
+		parser.line(i, &line)?;
+		if parser.done {
+			break;
+		}
+	}
+	parser.finish()
+}
+
+impl Solver {
+	/// Creates a solver from the text in the DIMACS CNF format.
+	///
+	/// Lines starting with `c` are comments. The clauses follow the
+	/// `p cnf <vars> <clauses>` header as integers separated by whitespaces,
+	/// each terminated by `0`.
+	pub fn from_dimacs(input: &str) -> Result<Solver, DimacsError> {
+		let (n, clauses) = parse(input)?;
+		Self::from_parsed(n, &clauses)
+	}
+
+	/// Creates a solver from the DIMACS CNF text like [`Solver::from_dimacs`],
+	/// keeping the names of the variables given by the comments of the form
+	/// `c var N = NAME`, which [`Solver::var_names`] returns. The other
+	/// comments are ignored, and so are the names of the variables out of
+	/// `1..=vars`. A later name of a variable replaces the earlier one.
+	pub fn from_dimacs_with_names(input: &str) -> Result<Solver, DimacsError> {
+		let mut parser = Parser {
+			names: Some(HashMap::new()),
+			..Parser::default()
+		};
+		for (i, line) in input.lines().enumerate() {
+			parser.line(i, line)?;
+		}
+		let mut names = parser.names.take().unwrap();
+		let (n, clauses) = parser.finish()?;
+		names.retain(|&var, _| var <= n);
+		let mut solver = Self::from_parsed(n, &clauses)?;
+		solver.var_names = names;
+		Ok(solver)
+	}
+
+	/// Creates a solver from the DIMACS CNF input like
+	/// [`Solver::from_dimacs`], reading it line by line.
+	pub fn from_dimacs_reader<R: Read>(reader: R) -> Result<Solver, DimacsError> {
+		let (n, clauses) = parse_reader(BufReader::new(reader))?;
+		Self::from_parsed(n, &clauses)
+	}
+
+	/// Creates a solver from the DIMACS CNF file, which is decompressed if its
+	/// name ends with `.gz`. Enabled by the `gzip` feature.
+	#[cfg(feature = "gzip")]
+	pub fn from_dimacs_path<P: AsRef<Path>>(path: P) -> Result<Solver, DimacsError> {
+		let path = path.as_ref();
+		let file = File::open(path)?;
+		if path.extension().is_some_and(|ext| ext == "gz") {
+			Self::from_dimacs_reader(GzDecoder::new(file))
+		} else {
+			Self::from_dimacs_reader(file)
+		}
+	}
+
+	/// The names of the variables read by [`Solver::from_dimacs_with_names`],
+	/// empty for a solver created otherwise.
+	pub fn var_names(&self) -> &HashMap<usize, String> {
+		&self.var_names
+	}
+
+	/// The model like [`Solver::model`], with the name of each variable, or
+	/// its number if it has no name.
+	pub fn named_model(&self) -> Option<Vec<(String, bool)>> {
+		let model = self.model()?;
+		Some(
+			model
+				.into_iter()
+				.enumerate()
+				.map(|(i, value)| {
+					let name = match self.var_names.get(&(i + 1)) {
+						Some(name) => name.clone(),
+						None => (i + 1).to_string(),
+					};
+					(name, value)
+				})
+				.collect(),
+		)
+	}
+
+	fn from_parsed(n: usize, clauses: &[Vec<LitInt>]) -> Result<Solver, DimacsError> {
+		// `parse` has checked the literals, which `try_new` accepts.
+		Solver::try_new(n, clauses).map_err(|error| DimacsError::InvalidClause(error.clause))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::{lcg, random_cnf};
+
+	#[test]
+	fn parse_test() {
+		let input = "c example\np cnf 3 3\n1 -2 0\n-1\n 2 3 0 c\n3 0\n";
+		assert_eq!(
+			parse(input),
+			Err(DimacsError::BadLiteral {
+				line: 5,
+				token: "c".to_owned()
+			})
+		);
+		let input = "c example\np cnf 3 3\n1 -2 0\n-1\n 2 3 0\n3 0\n";
+		assert_eq!(
+			parse(input),
+			Ok((3, vec![vec![1, -2], vec![-1, 2, 3], vec![3]]))
+		);
+		assert_eq!(parse("p cnf 2 1\n1 2\n"), Ok((2, vec![vec![1, 2]])));
+		assert_eq!(parse("p cnf 2 1\n1 2 0\n%\n0\n"), Ok((2, vec![vec![1, 2]])));
+	}
+
+	#[test]
+	fn parse_error_test() {
+		assert_eq!(parse("1 2 0\n"), Err(DimacsError::MissingHeader));
+		assert_eq!(parse(""), Err(DimacsError::MissingHeader));
+		assert_eq!(parse("p cnf 2\n"), Err(DimacsError::MissingHeader));
+		assert_eq!(
+			parse("p cnf 2 1\n1 3 0\n"),
+			Err(DimacsError::BadLiteral {
+				line: 2,
+				token: "3".to_owned()
+			})
+		);
+		assert_eq!(
+			parse("p cnf 2 2\n1 2 0\n"),
+			Err(DimacsError::CountMismatch {
+				expected: 2,
+				found: 1
+			})
+		);
+	}
+
+	#[test]
+	fn from_dimacs_test() {
+		let mut ss = Solver::from_dimacs("p cnf 3 2\n1 -2 0\n-1 2 3 0\n").unwrap();
+		assert!(ss.solve().is_sat());
+		// The repeated literal is kept once.
+		let ss = Solver::from_dimacs("p cnf 3 2\n1 -2 0\n-1 2 -1 0\n").unwrap();
+		assert_eq!(ss.to_clauses(), vec![vec![1, -2], vec![-1, 2]]);
+	}
+
+	#[test]
+	fn from_dimacs_reader_test() {
+		let input = "p cnf 3 2\n1 -2 0\n-1\n2 3 0\n";
+		let ss = Solver::from_dimacs_reader(input.as_bytes()).unwrap();
+		assert_eq!(
+			ss.to_string(),
+			Solver::from_dimacs(input).unwrap().to_string()
+		);
+		// The garbage after `%` is not read.
+		assert!(Solver::from_dimacs_reader(&b"p cnf 1 1\n1 0\n%\n\xff\n"[..]).is_ok());
+		assert_eq!(
+			Solver::from_dimacs_reader(&b"p cnf 1 1\n1 0\n"[..])
+				.unwrap()
+				.num_clauses(),
+			1
+		);
+		assert_eq!(
+			Solver::from_dimacs_reader(&b"p cnf 2 1\n1 3 0\n"[..]).err(),
+			Some(DimacsError::BadLiteral {
+				line: 2,
+				token: "3".to_owned()
+			})
+		);
+		match Solver::from_dimacs_reader(&b"p cnf 1 1\n\xff 0\n"[..]) {
+			Err(DimacsError::Io { kind, .. }) => assert_eq!(kind, io::ErrorKind::InvalidData),
+			_ => panic!("invalid UTF-8 is accepted"),
+		}
+	}
+
+	#[test]
+	fn round_trip_test() {
+		let mut next = lcg(2718);
+		for round in 0..60 {
+			let n = match round % 3 {
+				0 => 1 + next(5) as usize,
+				1 => 1 + next(100) as usize,
+				_ => 100_000 + next(100_000) as usize,
+			};
+			let count = if round % 10 == 9 { 2000 } else { next(30) };
+			let mut clauses = random_cnf(next(1 << 32), n, count as usize, false);
+			// The largest variable, with the most digits.
+			clauses.push(vec![-(n as LitInt)]);
+			// As written, without repeated literals and tautologies.
+			let clauses = Solver::new(n, &clauses).unwrap().to_clauses();
+			let text = Solver::new(n, &clauses).unwrap().to_string();
+			assert_eq!(parse(&text), Ok((n, clauses.clone())));
+			// Comments between the lines, and clauses split across lines.
+			let mut mangled = String::new();
+			for line in text.lines() {
+				if next(3) == 0 {
+					mangled.push_str("c a comment 1 2 0\n");
+				}
+				if line.starts_with('p') || next(2) == 0 {
+					mangled.push_str(line);
+				} else {
+					mangled.push_str(&line.replace(' ', "\n  "));
+				}
+				mangled.push('\n');
+			}
+			mangled.push_str("c\n");
+			let restored = Solver::from_dimacs(&mangled).unwrap();
+			assert_eq!(restored.to_clauses(), clauses);
+			assert_eq!(restored.to_string(), text);
+		}
+
+		// The largest literals.
+		let text = format!(
+			"p cnf {0} 2\n{0} -{1} 0\n-{0} 0\n",
+			LitInt::MAX,
+			LitInt::MAX - 1
+		);
+		assert_eq!(
+			parse(&text),
+			Ok((
+				LitInt::MAX as usize,
+				vec![vec![LitInt::MAX, -(LitInt::MAX - 1)], vec![-LitInt::MAX]]
+			))
+		);
+	}
+
+	#[test]
+	fn var_names_test() {
+		let input = "c var 1 = enabled_feature_x\n\
+			c var 3 =  needs network \n\
+			c the names below are ignored\n\
+			c var 0 = zero\n\
+			c var 4 = out_of_range\n\
+			c var x = bad\nc var 2 =\n\
+			p cnf 3 2\n\
+			1 -3 0\n\
+			c var 3 = needs_network\n\
+			-1 0\n";
+		let mut ss = Solver::from_dimacs_with_names(input).unwrap();
+		let names: HashMap<usize, String> = vec![
+			(1, "enabled_feature_x".to_owned()),
+			(3, "needs_network".to_owned()),
+		]
+		.into_iter()
+		.collect();
+		assert_eq!(ss.var_names(), &names);
+		assert!(Solver::from_dimacs(input).unwrap().var_names().is_empty());
+		assert_eq!(ss.named_model(), None);
+		assert!(ss.solve().is_sat());
+		assert_eq!(
+			ss.named_model(),
+			Some(vec![
+				("enabled_feature_x".to_owned(), false),
+				("2".to_owned(), false),
+				("needs_network".to_owned(), false),
+			])
+		);
+		let text = format!("{:#}", ss);
+		assert_eq!(
+			text,
+			"c var 1 = enabled_feature_x\nc var 3 = needs_network\np cnf 3 2\n1 -3 0\n-1 0\n"
+		);
+		assert_eq!(ss.to_string(), "p cnf 3 2\n1 -3 0\n-1 0\n");
+		let restored = Solver::from_dimacs_with_names(&text).unwrap();
+		assert_eq!(restored.var_names(), &names);
+	}
+
+	#[cfg(feature = "gzip")]
+	#[test]
+	fn from_dimacs_path_test() {
+		use flate2::write::GzEncoder;
+		use std::io::Write;
+		let input = "p cnf 2 2\n1 2 0\n-1 0\n";
+		let dir = std::env::temp_dir();
+		let plain = dir.join(format!("yasuosat-{}.cnf", std::process::id()));
+		let gz = dir.join(format!("yasuosat-{}.cnf.gz", std::process::id()));
+		std::fs::write(&plain, input).unwrap();
+		let mut encoder = GzEncoder::new(File::create(&gz).unwrap(), Default::default());
+		encoder.write_all(input.as_bytes()).unwrap();
+		encoder.finish().unwrap();
+		for path in [&plain, &gz] {
+			let mut ss = Solver::from_dimacs_path(path).unwrap();
+			assert!(ss.solve().is_sat());
+			assert_eq!(ss.model(), Some(vec![false, true]));
+			std::fs::remove_file(path).unwrap();
+		}
+		assert!(matches!(
+			Solver::from_dimacs_path(&plain),
+			Err(DimacsError::Io {
+				kind: io::ErrorKind::NotFound,
+				..
+			})
+		));
+	}
+}
@@ -0,0 +1,139 @@
+//! The C API, enabled by the `ffi` feature.
+//!
+//! A solver is referred by an opaque `*mut Solver` handle created by
+//! [`yasuosat_new`] and destroyed by [`yasuosat_free`]. The declarations for
+//! C are in `include/yasuosat.h`. Every function accepts a null handle and
+//! does nothing on it.
+
+use crate::{LitInt, SolveResult, Solver};
+use std::os::raw::c_int;
+
+/// Converts the literal from C, which is narrower than `LitInt` with the
+/// `wide-literals` feature.
+#[allow(clippy::useless_conversion)]
+fn to_lit(lit: c_int) -> LitInt {
+	LitInt::from(lit)
+}
+
+/// Creates a solver with `n` variables and no clauses.
+#[no_mangle]
+pub extern "C" fn yasuosat_new(n: usize) -> *mut Solver {
+	let solver = Solver::new(n, &[]).expect("no clause to check");
+	Box::into_raw(Box::new(solver))
+}
+
+/// Adds the clause of `len` literals at `lits`. Returns `0` on success, and
+/// `-1` if the handle is null or the clause is invalid, in which case the
+/// solver is not changed.
+///
+/// # Safety
+///
+/// `solver` must be null or a live handle, and `lits` must point `len`
+/// literals unless `len` is `0`.
+#[no_mangle]
+pub unsafe extern "C" fn yasuosat_add_clause(
+	solver: *mut Solver,
+	lits: *const c_int,
+	len: usize,
+) -> c_int {
+	let solver = match solver.as_mut() {
+		Some(solver) => solver,
+		None => return -1,
+	};
+	let clause: Vec<LitInt> = if len == 0 {
+		Vec::new()
+	} else if lits.is_null() {
+		return -1;
+	} else {
+		std::slice::from_raw_parts(lits, len)
+			.iter()
+			.map(|&lit| to_lit(lit))
+			.collect()
+	};
+	match solver.add_clause(&clause) {
+		Ok(()) => 0,
+		Err(_) => -1,
+	}
+}
+
+/// Solves the formula. Returns `10` if it is satisfiable, `20` if it is
+/// unsatisfiable, and `0` if unknown or the handle is null.
+///
+/// # Safety
+///
+/// `solver` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn yasuosat_solve(solver: *mut Solver) -> c_int {
+	match solver.as_mut().map(Solver::solve) {
+		Some(SolveResult::Sat) => 10,
+		Some(SolveResult::Unsat) => 20,
+		Some(SolveResult::Unknown) | None => 0,
+	}
+}
+
+/// The value of the variable in the model: `var` if true and `-var` if
+/// false. Returns `0` if there is no model, the handle is null, or `var` is
+/// out of `1..=n`.
+///
+/// # Safety
+///
+/// `solver` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn yasuosat_value(solver: *const Solver, var: c_int) -> c_int {
+	let solver = match solver.as_ref() {
+		Some(solver) => solver,
+		None => return 0,
+	};
+	if var <= 0 || var as usize > solver.num_vars() {
+		return 0;
+	}
+	match solver.model_value(to_lit(var)) {
+		Some(true) => var,
+		Some(false) => -var,
+		None => 0,
+	}
+}
+
+/// Destroys the solver. The handle must not be used afterwards.
+///
+/// # Safety
+///
+/// `solver` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn yasuosat_free(solver: *mut Solver) {
+	if !solver.is_null() {
+		drop(Box::from_raw(solver));
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::ptr;
+
+	#[test]
+	fn ffi_test() {
+		unsafe {
+			let ss = yasuosat_new(3);
+			assert_eq!(yasuosat_add_clause(ss, [1, -2].as_ptr(), 2), 0);
+			assert_eq!(yasuosat_add_clause(ss, [-1].as_ptr(), 1), 0);
+			assert_eq!(yasuosat_add_clause(ss, [2, 4].as_ptr(), 2), -1);
+			assert_eq!(yasuosat_add_clause(ss, ptr::null(), 2), -1);
+			assert_eq!(yasuosat_value(ss, 1), 0);
+			assert_eq!(yasuosat_solve(ss), 10);
+			assert_eq!(yasuosat_value(ss, 1), -1);
+			assert_eq!(yasuosat_value(ss, 2), -2);
+			assert_eq!(yasuosat_value(ss, 4), 0);
+			assert_eq!(yasuosat_add_clause(ss, [2, 3].as_ptr(), 2), 0);
+			assert_eq!(yasuosat_add_clause(ss, ptr::null(), 0), 0);
+			assert_eq!(yasuosat_solve(ss), 20);
+			yasuosat_free(ss);
+
+			let null = ptr::null_mut();
+			assert_eq!(yasuosat_add_clause(null, [1].as_ptr(), 1), -1);
+			assert_eq!(yasuosat_solve(null), 0);
+			assert_eq!(yasuosat_value(null, 1), 0);
+			yasuosat_free(null);
+		}
+	}
+}
@@ -0,0 +1,129 @@
+//! Binary max-heap of variables ordered by their activities.
+
+#[derive(Clone)]
+pub(crate) struct VarHeap {
+	/// The variables in the heap order.
+	heap: Vec<usize>,
+	/// The index of each variable in `heap`, `usize::MAX` if not contained.
+	indices: Vec<usize>,
+}
+
+impl VarHeap {
+	/// Creates an empty heap for the variables `1..=n`.
+	pub(crate) fn new(n: usize) -> Self {
+		VarHeap {
+			heap: Vec::with_capacity(n),
+			indices: vec![usize::MAX; n + 1],
+		}
+	}
+
+	/// Makes room for the variable `n + 1`, which is not inserted.
+	pub(crate) fn grow(&mut self) {
+		self.indices.push(usize::MAX);
+	}
+
+	pub(crate) fn len(&self) -> usize {
+		self.heap.len()
+	}
+
+	pub(crate) fn is_empty(&self) -> bool {
+		self.heap.is_empty()
+	}
+
+	/// The variable at the index `i` of the heap order.
+	pub(crate) fn get(&self, i: usize) -> usize {
+		self.heap[i]
+	}
+
+	#[inline]
+	pub(crate) fn contains(&self, var: usize) -> bool {
+		self.indices[var] != usize::MAX
+	}
+
+	pub(crate) fn insert(&mut self, var: usize, activity: &[f64]) {
+		if self.contains(var) {
+			return;
+		}
+		self.indices[var] = self.heap.len();
+		self.heap.push(var);
+		self.up(self.heap.len() - 1, activity);
+	}
+
+	/// Restores the order after the activity of `var` is increased.
+	pub(crate) fn increased(&mut self, var: usize, activity: &[f64]) {
+		if self.contains(var) {
+			self.up(self.indices[var], activity);
+		}
+	}
+
+	/// Removes and returns the variable with the highest activity.
+	pub(crate) fn pop(&mut self, activity: &[f64]) -> Option<usize> {
+		let top = *self.heap.first()?;
+		let last = self.heap.pop().unwrap();
+		self.indices[top] = usize::MAX;
+		if !self.heap.is_empty() {
+			self.heap[0] = last;
+			self.indices[last] = 0;
+			self.down(0, activity);
+		}
+		Some(top)
+	}
+
+	fn up(&mut self, mut i: usize, activity: &[f64]) {
+		let var = self.heap[i];
+		while i > 0 {
+			let parent = (i - 1) / 2;
+			if activity[self.heap[parent]] >= activity[var] {
+				break;
+			}
+			self.heap[i] = self.heap[parent];
+			self.indices[self.heap[i]] = i;
+			i = parent;
+		}
+		self.heap[i] = var;
+		self.indices[var] = i;
+	}
+
+	fn down(&mut self, mut i: usize, activity: &[f64]) {
+		let var = self.heap[i];
+		loop {
+			let mut child = 2 * i + 1;
+			if child >= self.heap.len() {
+				break;
+			}
+			if child + 1 < self.heap.len()
+				&& activity[self.heap[child + 1]] > activity[self.heap[child]]
+			{
+				child += 1;
+			}
+			if activity[self.heap[child]] <= activity[var] {
+				break;
+			}
+			self.heap[i] = self.heap[child];
+			self.indices[self.heap[i]] = i;
+			i = child;
+		}
+		self.heap[i] = var;
+		self.indices[var] = i;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn heap_test() {
+		let mut activity = vec![0.0, 3.0, 1.0, 4.0, 1.5, 9.0];
+		let mut heap = VarHeap::new(5);
+		for var in 1..=5 {
+			heap.insert(var, &activity);
+		}
+		assert_eq!(heap.pop(&activity), Some(5));
+		activity[2] = 10.0;
+		heap.increased(2, &activity);
+		let order: Vec<_> = std::iter::from_fn(|| heap.pop(&activity)).collect();
+		assert_eq!(order, vec![2, 3, 1, 4]);
+		assert!(!heap.contains(1));
+	}
+}
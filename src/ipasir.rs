@@ -0,0 +1,282 @@
+//! The IPASIR incremental C API, enabled by the `ipasir` feature.
+//!
+//! IPASIR does not tell the number of variables in advance, so the solver is
+//! grown by [`Solver::new_var`] when a new variable appears.
+//! `ipasir_set_learn` is not provided.
+
+use crate::{LitInt, SolveResult, Solver};
+use std::os::raw::{c_char, c_int, c_void};
+
+/// The state behind an IPASIR handle.
+struct Ipasir {
+	solver: Solver,
+	/// The clause being added by `ipasir_add`.
+	clause: Vec<LitInt>,
+	/// The assumptions for the next `ipasir_solve`.
+	assumptions: Vec<LitInt>,
+	/// The callback set by `ipasir_set_terminate` with its argument.
+	terminate: Option<(Terminate, *mut c_void)>,
+}
+
+type Terminate = extern "C" fn(*mut c_void) -> c_int;
+
+impl Ipasir {
+	fn new() -> Self {
+		Ipasir {
+			solver: Solver::new(0, &[]).unwrap(),
+			clause: Vec::new(),
+			assumptions: Vec::new(),
+			terminate: None,
+		}
+	}
+
+	fn see(&mut self, lit: LitInt) {
+		while self.solver.num_vars() < lit.unsigned_abs() as usize {
+			self.solver.new_var();
+		}
+	}
+
+	fn add(&mut self, lit: LitInt) {
+		if lit != 0 {
+			self.see(lit);
+			self.clause.push(lit);
+			return;
+		}
+		// IPASIR allows the same literal twice, which is kept once. Every
+		// literal is seen above, so the clause is in range, and it would be
+		// dropped rather than panic across the C boundary otherwise.
+		let clause = std::mem::take(&mut self.clause);
+		let _ = self.solver.add_clause(&clause);
+	}
+
+	fn solve(&mut self) -> SolveResult {
+		match self.terminate {
+			Some((terminate, data)) => self
+				.solver
+				.set_terminate_callback(move || terminate(data) != 0),
+			None => self.solver.clear_terminate_callback(),
+		}
+		let result = self.solver.solve_with_assumptions(&self.assumptions);
+		self.assumptions.clear();
+		result
+	}
+
+	fn val(&self, lit: LitInt) -> LitInt {
+		if lit.unsigned_abs() as usize > self.solver.num_vars() {
+			return 0;
+		}
+		match self.solver.model_value(lit) {
+			Some(true) => lit,
+			Some(false) => -lit,
+			None => 0,
+		}
+	}
+}
+
+/// Converts the literal from C, which is narrower than `LitInt` with the
+/// `wide-literals` feature.
+#[allow(clippy::useless_conversion)]
+fn to_lit(lit: c_int) -> LitInt {
+	LitInt::from(lit)
+}
+
+/// The name and the version of the solver.
+#[no_mangle]
+pub extern "C" fn ipasir_signature() -> *const c_char {
+	concat!("yasuosat-", env!("CARGO_PKG_VERSION"), "\0").as_ptr() as *const c_char
+}
+
+/// Creates a solver, which is in the input state.
+#[no_mangle]
+pub extern "C" fn ipasir_init() -> *mut c_void {
+	Box::into_raw(Box::new(Ipasir::new())) as *mut c_void
+}
+
+/// Destroys the solver.
+///
+/// # Safety
+///
+/// `solver` must be null or a live handle, which is not used afterwards.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_release(solver: *mut c_void) {
+	if !solver.is_null() {
+		drop(Box::from_raw(solver as *mut Ipasir));
+	}
+}
+
+/// Adds the literal to the current clause, or finishes it if `lit` is `0`.
+/// `c_int::MIN`, which has no negation, is ignored.
+///
+/// # Safety
+///
+/// `solver` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_add(solver: *mut c_void, lit: c_int) {
+	match (solver as *mut Ipasir).as_mut() {
+		Some(solver) if lit != c_int::MIN => solver.add(to_lit(lit)),
+		_ => (),
+	}
+}
+
+/// Assumes the literal in the next `ipasir_solve`. `0` and `c_int::MIN` are
+/// ignored.
+///
+/// # Safety
+///
+/// `solver` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_assume(solver: *mut c_void, lit: c_int) {
+	if let Some(solver) = (solver as *mut Ipasir).as_mut() {
+		if lit != 0 && lit != c_int::MIN {
+			solver.see(to_lit(lit));
+			solver.assumptions.push(to_lit(lit));
+		}
+	}
+}
+
+/// Solves the formula under the assumptions, which are cleared. Returns `10`
+/// if it is satisfiable, `20` if unsatisfiable, and `0` otherwise.
+///
+/// # Safety
+///
+/// `solver` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_solve(solver: *mut c_void) -> c_int {
+	match (solver as *mut Ipasir).as_mut().map(Ipasir::solve) {
+		Some(SolveResult::Sat) => 10,
+		Some(SolveResult::Unsat) => 20,
+		Some(SolveResult::Unknown) | None => 0,
+	}
+}
+
+/// The value of the literal after a satisfiable solve: `lit` if true, and
+/// `-lit` if false. Returns `0` if unknown.
+///
+/// # Safety
+///
+/// `solver` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_val(solver: *mut c_void, lit: c_int) -> c_int {
+	match (solver as *const Ipasir).as_ref() {
+		Some(solver) if lit != 0 => solver.val(to_lit(lit)) as c_int,
+		_ => 0,
+	}
+}
+
+/// Sets the callback polled during `ipasir_solve`, which stops the solve by
+/// returning non-zero. `terminate` is called with `data`. A null `terminate`
+/// removes the callback.
+///
+/// # Safety
+///
+/// `solver` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_set_terminate(
+	solver: *mut c_void,
+	data: *mut c_void,
+	terminate: Option<Terminate>,
+) {
+	if let Some(solver) = (solver as *mut Ipasir).as_mut() {
+		solver.terminate = terminate.map(|terminate| (terminate, data));
+	}
+}
+
+/// Returns `1` if the assumption `lit` was used to prove the last
+/// unsatisfiable solve, and `0` otherwise.
+///
+/// # Safety
+///
+/// `solver` must be null or a live handle.
+#[no_mangle]
+pub unsafe extern "C" fn ipasir_failed(solver: *mut c_void, lit: c_int) -> c_int {
+	match (solver as *const Ipasir).as_ref() {
+		Some(solver) => solver.solver.failed_assumptions().contains(&to_lit(lit)) as c_int,
+		None => 0,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::ffi::CStr;
+
+	#[test]
+	fn ipasir_test() {
+		unsafe {
+			assert!(CStr::from_ptr(ipasir_signature())
+				.to_str()
+				.unwrap()
+				.starts_with("yasuosat-"));
+			let ss = ipasir_init();
+			for lit in [1, -2, 1, 0, 2, 3, 0] {
+				ipasir_add(ss, lit);
+			}
+			assert_eq!(ipasir_solve(ss), 10);
+			assert_eq!(ipasir_val(ss, 4), 0);
+			ipasir_assume(ss, -1);
+			ipasir_assume(ss, 2);
+			assert_eq!(ipasir_solve(ss), 20);
+			assert_eq!(ipasir_failed(ss, -1), 1);
+			assert_eq!(ipasir_failed(ss, 2), 1);
+			// The assumptions are cleared.
+			assert_eq!(ipasir_solve(ss), 10);
+			// A new variable grows the solver.
+			for lit in [-1, 4, 0, -4, 0] {
+				ipasir_add(ss, lit);
+			}
+			assert_eq!(ipasir_solve(ss), 10);
+			assert_eq!(ipasir_val(ss, 1), -1);
+			assert_eq!(ipasir_val(ss, -2), -2);
+			assert_eq!(ipasir_val(ss, 3), 3);
+			assert_eq!(ipasir_val(ss, -4), -4);
+			// `c_int::MIN` does not grow the solver.
+			ipasir_add(ss, c_int::MIN);
+			ipasir_assume(ss, c_int::MIN);
+			assert_eq!(ipasir_solve(ss), 10);
+			assert_eq!(ipasir_val(ss, 5), 0);
+			ipasir_assume(ss, -3);
+			assert_eq!(ipasir_solve(ss), 20);
+			assert_eq!(ipasir_failed(ss, -3), 1);
+			ipasir_add(ss, 0);
+			assert_eq!(ipasir_solve(ss), 20);
+			assert_eq!(ipasir_failed(ss, -3), 0);
+			ipasir_release(ss);
+		}
+	}
+
+	extern "C" fn stop(data: *mut c_void) -> c_int {
+		unsafe { *(data as *mut usize) += 1 };
+		1
+	}
+
+	#[test]
+	fn ipasir_terminate_test() {
+		unsafe {
+			let ss = ipasir_init();
+			// The pigeonhole formula of 8 pigeons in 7 holes.
+			let var = |i: c_int, j: c_int| i * 7 + j + 1;
+			for i in 0..8 {
+				for j in 0..7 {
+					ipasir_add(ss, var(i, j));
+				}
+				ipasir_add(ss, 0);
+			}
+			for j in 0..7 {
+				for i in 0..8 {
+					for k in 0..i {
+						ipasir_add(ss, -var(i, j));
+						ipasir_add(ss, -var(k, j));
+						ipasir_add(ss, 0);
+					}
+				}
+			}
+			let mut polls = 0usize;
+			ipasir_set_terminate(ss, &mut polls as *mut usize as *mut c_void, Some(stop));
+			assert_eq!(ipasir_solve(ss), 0);
+			assert_eq!(polls, 1);
+			ipasir_set_terminate(ss, std::ptr::null_mut(), None);
+			assert_eq!(ipasir_solve(ss), 20);
+			ipasir_release(ss);
+		}
+	}
+}
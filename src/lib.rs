@@ -0,0 +1,6499 @@
+//! yasuosat is a small SAT solver.
+//!
+//! The clauses given at construction time are stored in one fixed heap, and
+//! the ones added later are allocated separately, so that each clause can be
+//! referred internally by raw pointers which are never invalidated. The API
+//! refers a clause by a [`ClauseRef`] instead.
+//! Literals are represented by [`Lit`], which is `NonZeroI32` (or `NonZeroI64`
+//! with the `wide-literals` feature), where `x` and `-x` are the positive and
+//! negative literal of the variable `x`.
+//!
+//! The search is deterministic: given the same clauses in the same order,
+//! the same assumptions and the same settings, including the seed, a solver
+//! makes the same decisions and gives the same model and [`SolverStats`].
+//! Nothing depends on the addresses of the clauses or on the order of a hash
+//! table. Only a search stopped by [`Solver::set_timeout`] or a terminate
+//! callback, and [`solve_portfolio`], which takes the first thread to
+//! decide, depend on the timing.
+
+mod assign;
+mod binary;
+mod builder;
+mod dimacs;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod heap;
+#[cfg(feature = "ipasir")]
+pub mod ipasir;
+mod maxsat;
+mod naive;
+mod portfolio;
+#[cfg(feature = "serde")]
+mod state;
+
+use assign::Assignment;
+pub use binary::BinaryError;
+pub use builder::SolverBuilder;
+pub use dimacs::DimacsError;
+use heap::VarHeap;
+pub use maxsat::MaxSatSolver;
+pub use portfolio::solve_portfolio;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::hash::{BuildHasherDefault, Hasher};
+use std::io::{self, Write};
+#[cfg(not(feature = "wide-literals"))]
+use std::num::NonZeroI32;
+#[cfg(feature = "wide-literals")]
+use std::num::NonZeroI64;
+use std::time::{Duration, Instant};
+
+/// The integer representation of literals in the API, `i32` by default.
+#[cfg(not(feature = "wide-literals"))]
+pub type LitInt = i32;
+/// The integer representation of literals in the API, `i64` with the
+/// `wide-literals` feature.
+#[cfg(feature = "wide-literals")]
+pub type LitInt = i64;
+
+/// A literal, the non-zero version of [`LitInt`].
+#[cfg(not(feature = "wide-literals"))]
+pub type Lit = NonZeroI32;
+/// A literal, the non-zero version of [`LitInt`].
+#[cfg(feature = "wide-literals")]
+pub type Lit = NonZeroI64;
+
+/// Pointers to the first and the last literal of a clause in the clause heap.
+///
+/// The literals at these two positions are the watched literals of the
+/// clause. Propagation swaps literals inside the clause to keep the watched
+/// literals on the ends, so the pointers themselves never change.
+type ClausePtr = (*mut Lit, *mut Lit);
+
+/// A multiplicative hash for the keys of `verify_invariant()`, which are
+/// pointers and small integers. It is much faster than the default one, whose
+/// resistance to collisions is not needed there.
+#[derive(Default)]
+struct FastHasher(u64);
+
+impl Hasher for FastHasher {
+	fn finish(&self) -> u64 {
+		self.0
+	}
+
+	fn write(&mut self, bytes: &[u8]) {
+		for &byte in bytes {
+			self.write_u64(byte.into());
+		}
+	}
+
+	fn write_u32(&mut self, n: u32) {
+		self.write_u64(n.into());
+	}
+
+	fn write_u64(&mut self, n: u64) {
+		self.0 = (self.0.rotate_left(5) ^ n).wrapping_mul(0x517c_c1b7_2722_0a95);
+	}
+
+	fn write_usize(&mut self, n: usize) {
+		self.write_u64(n as u64);
+	}
+}
+
+/// A clause of a [`Solver`], which is the index of an original or a learned
+/// clause.
+///
+/// It stays valid while clauses are only added. Once clauses are removed, by
+/// [`Solver::eliminate_pure_literals`] or by the reduction of the learned
+/// clauses during a solve, it may refer another clause or none, in which
+/// case the methods taking it panic. It never dangles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ClauseRef {
+	learned: bool,
+	index: usize,
+}
+
+impl ClauseRef {
+	/// Whether the clause is learned, rather than one of the given clauses.
+	pub fn is_learned(&self) -> bool {
+		self.learned
+	}
+}
+
+/// Helper methods on literals.
+pub trait LitFunctions {
+	/// The literal as a signed integer.
+	fn as_int(&self) -> LitInt;
+	/// The literal as `i32`.
+	///
+	/// # Panics
+	///
+	/// Panics if the literal is out of the range of `i32`, which can happen
+	/// only with the `wide-literals` feature.
+	fn as_i32(&self) -> i32;
+	/// The variable of the literal, which is in `1..=n`.
+	fn var(&self) -> usize;
+	/// The index of the literal in a `2(n+1)` vector, that is `2 var` for the
+	/// positive literal and `2 var + 1` for the negative one. It does not
+	/// depend on `n`, so the indices stay when a variable is added.
+	fn get_loc(&self) -> usize;
+}
+
+impl LitFunctions for Lit {
+	#[inline]
+	fn as_int(&self) -> LitInt {
+		self.get()
+	}
+
+	#[cfg(not(feature = "wide-literals"))]
+	#[inline]
+	fn as_i32(&self) -> i32 {
+		self.get()
+	}
+
+	#[cfg(feature = "wide-literals")]
+	#[inline]
+	fn as_i32(&self) -> i32 {
+		use std::convert::TryFrom;
+		i32::try_from(self.get()).expect("literal out of the range of i32")
+	}
+
+	#[inline]
+	fn var(&self) -> usize {
+		self.as_int().unsigned_abs() as usize
+	}
+
+	#[inline]
+	fn get_loc(&self) -> usize {
+		2 * self.var() + (self.as_int() < 0) as usize
+	}
+}
+
+/// Learned clauses are reduced first after this number of conflicts, and the
+/// interval grows by `REDUCE_INC` each time.
+const REDUCE_FIRST: u64 = 2000;
+const REDUCE_INC: u64 = 300;
+
+/// The seed of the random decisions unless [`Solver::set_seed`] is called.
+const DEFAULT_SEED: u64 = 91648253;
+
+/// The number of conflicts between the checks of the timeout and the
+/// terminate callback.
+const CHECK_INTERVAL: u64 = 1024;
+
+/// The weights of a new LBD in the short and the long term averages of the
+/// Glucose restarts.
+const LBD_FAST_ALPHA: f64 = 1.0 / 32.0;
+const LBD_SLOW_ALPHA: f64 = 1.0 / 4096.0;
+/// A Glucose restart happens when the short term average exceeds the long
+/// term one by this factor, after this number of conflicts since the last
+/// restart.
+const GLUCOSE_MARGIN: f64 = 1.25;
+const GLUCOSE_MIN_CONFLICTS: usize = 50;
+
+/// The number of restarts before the first rephasing of
+/// [`Solver::set_rephase`], which is also the amount by which the interval
+/// grows each time.
+const REPHASE_INTERVAL: u64 = 8;
+
+/// The backjump distance above which the solver backtracks chronologically
+/// with [`Solver::set_chronological`], as suggested by Nadel and Ryvchin.
+const CHRONO_THRESHOLD: usize = 100;
+
+/// Literals allocated by `Box`, which are accessed only through raw pointers.
+///
+/// It is kept as a raw pointer rather than a `Box`, because moving a `Box`
+/// asserts that it is the unique reference, which invalidates the pointers
+/// derived from it under the Stacked Borrows model (and is reported by Miri).
+struct ClauseBody(*mut [Lit]);
+
+impl ClauseBody {
+	fn new(body: Box<[Lit]>) -> Self {
+		ClauseBody(Box::into_raw(body))
+	}
+
+	/// The pointer to the first literal.
+	#[inline]
+	fn base(&self) -> *mut Lit {
+		self.0 as *mut Lit
+	}
+
+	/// The pointers to the first and the last literal of the whole body,
+	/// which must not be empty.
+	fn ptr(&self) -> ClausePtr {
+		debug_assert!(!self.0.is_empty());
+		(self.base(), unsafe { self.base().add(self.0.len() - 1) })
+	}
+}
+
+impl Drop for ClauseBody {
+	fn drop(&mut self) {
+		drop(unsafe { Box::from_raw(self.0) });
+	}
+}
+
+/// A learned clause.
+struct Learned {
+	/// Owns the literals, which are accessed through `ptr`.
+	#[allow(dead_code)]
+	body: ClauseBody,
+	ptr: ClausePtr,
+	/// Literal Block Distance, the number of distinct decision levels in the
+	/// clause when it is learned.
+	lbd: usize,
+}
+
+/// Errors in a clause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClauseError {
+	/// The clause has `0` as a literal.
+	ZeroLiteral,
+	/// The variable of the literal is larger than the number of variables.
+	OutOfRange(LitInt),
+	/// The literals have the same literal twice, which is rejected by
+	/// [`Solver::add_xor`] and [`Solver::add_at_most_k`]. A clause keeps it
+	/// once.
+	DuplicateLiteral(LitInt),
+	/// There is no model to block, see [`Solver::block_current_model`].
+	NoModel,
+}
+
+impl fmt::Display for ClauseError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ClauseError::ZeroLiteral => write!(f, "zero literal"),
+			ClauseError::OutOfRange(lit) => write!(f, "literal {} out of range", lit),
+			ClauseError::DuplicateLiteral(lit) => write!(f, "literal {} appears twice", lit),
+			ClauseError::NoModel => write!(f, "no model to block"),
+		}
+	}
+}
+
+impl std::error::Error for ClauseError {}
+
+/// The error of [`Solver::try_new`] and [`Solver::from_clauses`], which is
+/// the first invalid clause.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BuildError {
+	/// The index of the clause in the input.
+	pub clause: usize,
+	pub error: ClauseError,
+}
+
+impl fmt::Display for BuildError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "clause {}: {}", self.clause, self.error)
+	}
+}
+
+impl std::error::Error for BuildError {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(&self.error)
+	}
+}
+
+/// The error of [`Solver::set_polarities`], whose input does not have one
+/// element for each variable.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolarityError {
+	/// The number of variables.
+	pub expected: usize,
+	/// The length of the input.
+	pub found: usize,
+}
+
+impl fmt::Display for PolarityError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(
+			f,
+			"{} polarities for {} variables",
+			self.found, self.expected
+		)
+	}
+}
+
+impl std::error::Error for PolarityError {}
+
+/// Checks the literals of the clause over `n` variables like
+/// `check_clause()`, but allows the same literal twice. Returns the clause
+/// with each literal at its first occurrence, or `None` if it is a
+/// tautology.
+///
+/// Every path taking clauses normalizes them here, the constructors, the
+/// builder, `add_clause`, the DIMACS parser through `try_new` and the MaxSAT
+/// solver, so that they treat the edge cases alike. The literals are not
+/// sorted, since the first and the last one are watched.
+fn normalize_clause(n: usize, clause: &[LitInt]) -> Result<Option<Cow<'_, [LitInt]>>, ClauseError> {
+	match check_clause(n, clause) {
+		Ok(true) => Ok(None),
+		Ok(false) => Ok(Some(Cow::Borrowed(clause))),
+		Err(ClauseError::DuplicateLiteral(_)) => {
+			let mut seen = HashSet::new();
+			let lits: Vec<_> = clause
+				.iter()
+				.copied()
+				.filter(|&lit| seen.insert(lit))
+				.collect();
+			// The literals are valid, and no literal is repeated.
+			match check_clause(n, &lits) {
+				Ok(true) => Ok(None),
+				_ => Ok(Some(Cow::Owned(lits))),
+			}
+		}
+		Err(error) => Err(error),
+	}
+}
+
+/// Checks the literals of the clause over `n` variables, and returns whether
+/// it is a tautology, which has both `x` and `-x`.
+fn check_clause(n: usize, clause: &[LitInt]) -> Result<bool, ClauseError> {
+	if let Some(&lit) = clause
+		.iter()
+		.find(|lit| **lit == 0 || lit.unsigned_abs() as usize > n)
+	{
+		return Err(if lit == 0 {
+			ClauseError::ZeroLiteral
+		} else {
+			ClauseError::OutOfRange(lit)
+		});
+	}
+	let mut sorted = clause.to_vec();
+	sorted.sort_unstable_by_key(|lit| (lit.unsigned_abs(), *lit));
+	let mut tautology = false;
+	for pair in sorted.windows(2) {
+		if pair[0] == pair[1] {
+			return Err(ClauseError::DuplicateLiteral(pair[0]));
+		}
+		tautology |= pair[0] == -pair[1];
+	}
+	Ok(tautology)
+}
+
+/// The result of [`Solver::propagate_once`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PropagateResult {
+	/// No conflict found.
+	Ok,
+	/// The clause is falsified under the current assignment.
+	Conflict(ClausePtr),
+}
+
+/// The result of [`Solver::solve`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SolveResult {
+	/// Satisfiable. The model is left in the solver.
+	Sat,
+	/// Unsatisfiable.
+	Unsat,
+	/// Not decided, because the search stopped before the end.
+	Unknown,
+}
+
+impl SolveResult {
+	pub fn is_sat(self) -> bool {
+		self == SolveResult::Sat
+	}
+
+	pub fn is_unsat(self) -> bool {
+		self == SolveResult::Unsat
+	}
+}
+
+/// When the search restarts, see [`Solver::set_restart_strategy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum RestartStrategy {
+	/// After the number of conflicts in the Luby sequence times the restart
+	/// base. This is the default.
+	#[default]
+	Luby,
+	/// When the LBDs of the recent learned clauses are worse than the long
+	/// term average, as Glucose does.
+	Glucose,
+	/// Never.
+	None,
+}
+
+/// Which learned clauses are deleted on a reduction, see
+/// [`Solver::set_reduce_strategy`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ReduceStrategy {
+	/// Those with the highest LBD, but for the ones with LBD of at most 2, as
+	/// Glucose does. This is the default.
+	#[default]
+	Lbd,
+	/// The least active ones, but for the binary ones, as MiniSat does. The
+	/// activity of a clause is bumped when the conflict analysis resolves
+	/// with it, and decays on each conflict.
+	Activity,
+}
+
+/// How the learned clauses are minimized, see [`Solver::set_ccmin_mode`].
+/// They correspond to the `ccmin-mode` 0, 1 and 2 of MiniSat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Minimization {
+	/// Not minimized.
+	None,
+	/// A literal is removed if the other literals of its reason are in the
+	/// clause or at the level 0.
+	Basic,
+	/// A literal is removed if it is implied by the others through any path
+	/// in the implication graph. This is the default.
+	#[default]
+	Deep,
+}
+
+/// Which values are kept in `suggest` on unassigning, see
+/// [`Solver::set_phase_saving`]. They correspond to the `phase-saving` 0, 1
+/// and 2 of MiniSat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PhaseSaving {
+	/// None, so the decisions always take the values in `suggest`.
+	None,
+	/// Only the values at the highest level unassigned.
+	Limited,
+	/// All the values. This is the default.
+	#[default]
+	Full,
+}
+
+/// How the activities start, see [`Solver::set_initial_order`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum InitialOrder {
+	/// All `0`, so that the variables are decided from the lowest one. This
+	/// is the default.
+	#[default]
+	None,
+	/// By the number of the occurrences in the clauses.
+	OccurrenceCount,
+	/// By the Jeroslow-Wang score, the sum of `2^-len` over the clauses of
+	/// the length `len` which contain the variable, which weights the
+	/// shorter clauses more.
+	JeroslowWang,
+}
+
+/// Counters of the work done by a solver and the size of its state, see
+/// [`Solver::stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolverStats {
+	/// The number of literals assigned, including decisions.
+	pub propagations: u64,
+	/// The number of watcher lists traversed, one for each assigned literal
+	/// processed by propagation.
+	pub traversals: u64,
+	/// The number of watches moved to another literal.
+	pub relocations: u64,
+	pub conflicts: u64,
+	pub decisions: u64,
+	/// The number of learned clauses currently kept.
+	pub learned: usize,
+	/// The number of literals currently assigned.
+	pub trail: usize,
+	pub restarts: u64,
+	/// The largest number of literals assigned at once.
+	pub max_trail: usize,
+	/// The number of clauses learned, including the deleted ones.
+	pub total_learned: u64,
+}
+
+type ProgressCallback = Box<dyn FnMut(&SolverStats)>;
+
+type DecisionCallback = Box<dyn FnMut(&Solver) -> Option<LitInt>>;
+
+pub struct Solver {
+	/// The number of variables.
+	n: usize,
+	/// Fixed heaps which hold the literals of the clauses, one for the clauses
+	/// given to `new` and one for each added clause. They are only owned here,
+	/// and accessed through `clauses` and `watchers`.
+	#[allow(dead_code)]
+	clause_body: Vec<ClauseBody>,
+	clauses: Vec<ClausePtr>,
+	/// Learned clauses, each allocated separately so that adding or deleting
+	/// one does not move the others.
+	learned: Vec<Learned>,
+	/// The number of conflicts at which learned clauses are reduced next.
+	next_reduce: u64,
+	/// `watchers[lit.get_loc()]` holds the clauses watching `-lit`, that is
+	/// the clauses to be visited when `lit` becomes true. Binary clauses are
+	/// in `binary` instead.
+	watchers: Vec<Vec<ClausePtr>>,
+	/// `binary[lit.get_loc()]` holds each binary clause with `-lit` and its
+	/// other literal, which is implied when `lit` becomes true.
+	binary: Vec<Vec<(Lit, ClausePtr)>>,
+	/// The literals of the unit clauses, which are not watched but assigned at
+	/// level 0 when the search starts.
+	units: Vec<Lit>,
+	/// Set if the formula is found unsatisfiable regardless of the decisions,
+	/// e.g. it has the empty clause.
+	unsat: bool,
+	/// Set if the empty clause is given, which is not stored in `clauses`.
+	empty: bool,
+	/// The clauses removed by the eliminations, each with the literal which
+	/// is made true if the clause is false, to extend the model in the
+	/// reverse order.
+	eliminated: Vec<(Lit, Vec<Lit>)>,
+	/// The clauses given to `new` and `add_clause`, which are kept once a
+	/// preprocessing removes or rewrites some of `clauses` and are `clauses`
+	/// themselves until then. See `to_clauses()`.
+	original: Option<Vec<Box<[Lit]>>>,
+	// `assign`, `levels` and `suggest` have `n + 1` elements and are indexed
+	// directly by `lit.var()`, so the index 0 is unused.
+	assign: Assignment,
+	/// Decision level of each assigned variable.
+	levels: Vec<usize>,
+	/// The clause which implied each assigned variable, `None` for decisions.
+	reasons: Vec<Option<ClausePtr>>,
+	/// The preferred value of each variable on decisions. It can be set to
+	/// hint a starting assignment, and is overwritten by the last assigned
+	/// value when the variable is unassigned (phase saving).
+	pub suggest: Vec<bool>,
+	/// Current decision level.
+	level: usize,
+	/// Assigned literals in the assignment order. `backjump` pops it by the
+	/// `levels` of the literals rather than by the start index of each level,
+	/// since a level may have no literal, for a satisfied assumption, and the
+	/// levels are not monotone with chronological backtracking.
+	trail: Vec<Lit>,
+	/// The literals in `trail[qhead..]` are not propagated yet.
+	qhead: usize,
+	/// Set if a literal on the trail may have a lower level than one before
+	/// it, which happens with chronological backtracking.
+	out_of_order: bool,
+	/// Work area of `analyze`, indexed by variable.
+	seen: Vec<bool>,
+	/// The variables kept by the eliminations, see `freeze()`.
+	frozen: Vec<bool>,
+	/// VSIDS score of each variable.
+	activity: Vec<f64>,
+	/// The amount added to the activity on a bump, which grows on each
+	/// conflict instead of decaying all the activities.
+	var_inc: f64,
+	var_decay: f64,
+	minimization: Minimization,
+	phase_saving: PhaseSaving,
+	/// See `set_rephase()`.
+	rephase: bool,
+	/// The number of rephasings, which selects the next pattern.
+	rephases: u64,
+	/// The number of restarts left until the next rephasing.
+	rephase_countdown: u64,
+	/// The values on the longest trail reached at a conflict since the last
+	/// rephasing to them, whose length is `best_trail`.
+	best_phase: Vec<bool>,
+	best_trail: usize,
+	restart_base: usize,
+	restart_strategy: RestartStrategy,
+	/// See `set_reduce_strategy()`.
+	reduce_strategy: ReduceStrategy,
+	/// The activity of each learned clause with `ReduceStrategy::Activity`.
+	clause_activity: HashMap<ClausePtr, f64>,
+	/// The amount added to the activity of a clause on a bump, which grows
+	/// instead of decaying all the activities.
+	clause_inc: f64,
+	/// See `set_clause_decay()`.
+	clause_decay: f64,
+	/// The restarts in the current solve and the conflicts since the last
+	/// one, which are kept between the calls of `solve_limited()`.
+	restarts: usize,
+	restart_conflicts: usize,
+	/// The exponential moving averages of the LBDs of the learned clauses,
+	/// updated on each conflict whatever the restart strategy is. `0.0`
+	/// until the first conflict.
+	lbd_fast: f64,
+	lbd_slow: f64,
+	/// The probability of deciding a random variable instead of the most
+	/// active one.
+	random_var_freq: f64,
+	/// The state of the xorshift generator for the random decisions, which is
+	/// never `0`.
+	rng: u64,
+	/// The backjump distance above which the solver backtracks only one
+	/// level, `None` if it always jumps.
+	chrono_threshold: Option<usize>,
+	/// See `set_inprocessing()`.
+	inprocessing: bool,
+	/// The number of conflicts so far.
+	conflicts: u64,
+	/// The number of decisions so far.
+	decisions: u64,
+	/// The maximum number of conflicts in one solve, `None` if unlimited.
+	conflict_budget: Option<u64>,
+	/// The time limit of one solve, `None` if unlimited.
+	timeout: Option<Duration>,
+	propagations: u64,
+	traversals: u64,
+	relocations: u64,
+	/// The counters of `stats()`, which are not used by the search.
+	total_restarts: u64,
+	max_trail: usize,
+	total_learned: u64,
+	/// The number of `reduce_learned` calls so far.
+	reductions: u64,
+	/// Set once a learned clause is deleted.
+	deleted_learned: bool,
+	/// Unassigned variables (and possibly some assigned ones) ordered by the
+	/// activity.
+	order: VarHeap,
+	/// The result of the last `solve()`, `None` if not solved yet.
+	status: Option<bool>,
+	/// The assumptions of the current solve.
+	assumptions: Vec<Lit>,
+	/// See `push_assumption()`.
+	assumption_stack: Vec<Lit>,
+	/// See `failed_assumptions()`.
+	failed: Vec<LitInt>,
+	/// See `set_core_tracking()`.
+	core_tracking: bool,
+	/// The original clauses from which each learned clause is derived while
+	/// the tracking is enabled, `None` if it is learned before.
+	cores: HashMap<ClausePtr, Option<Vec<ClausePtr>>>,
+	/// The same for each literal in `units`.
+	unit_cores: HashMap<Lit, Option<Vec<ClausePtr>>>,
+	/// The original clauses which made the last solve unsatisfiable, `None`
+	/// if unknown. See `unsat_core()`.
+	final_core: Option<Vec<ClausePtr>>,
+	/// DRAT proof lines recorded during `solve_with_proof()`.
+	proof: Option<Vec<u8>>,
+	/// The names of the variables given by `c var N = NAME` comments, see
+	/// `from_dimacs_with_names()`.
+	var_names: HashMap<usize, String>,
+	/// See `set_terminate_callback()`.
+	terminate: Option<Box<dyn FnMut() -> bool>>,
+	/// See `set_progress_callback()`.
+	progress: Option<ProgressCallback>,
+	/// See `set_decision_fn()`.
+	decision: Option<DecisionCallback>,
+}
+
+impl Solver {
+	/// Creates a solver with `n` variables.
+	///
+	/// Returns `None` if some clause has a literal which is zero or out of
+	/// `-n..=n`. A literal repeated in a clause is kept once. An empty clause
+	/// is accepted and makes the formula unsatisfiable. Tautologies, which
+	/// have both `x` and `-x`, are always satisfied and dropped.
+	pub fn new(n: usize, clauses: &[Vec<LitInt>]) -> Option<Self> {
+		Self::try_new(n, clauses).ok()
+	}
+
+	/// Creates a solver with `n` variables like [`Solver::new`], but tells
+	/// which clause is invalid and why.
+	pub fn try_new(n: usize, clauses: &[Vec<LitInt>]) -> Result<Self, BuildError> {
+		Self::from_clauses(n, clauses)
+	}
+
+	/// Creates a solver with `n` variables like [`Solver::try_new`], taking
+	/// the clauses from an iterator. Each clause is checked and packed as it
+	/// arrives, so that clauses generated lazily are not all kept twice.
+	pub fn from_clauses<I>(n: usize, clauses: I) -> Result<Self, BuildError>
+	where
+		I: IntoIterator,
+		I::Item: AsRef<[LitInt]>,
+	{
+		let mut lits = Vec::new();
+		let mut lens = Vec::new();
+		for (i, clause) in clauses.into_iter().enumerate() {
+			match normalize_clause(n, clause.as_ref()) {
+				Ok(Some(clause)) => {
+					lits.extend(clause.iter().map(|&lit| unsafe { Lit::new_unchecked(lit) }));
+					lens.push(clause.len());
+				}
+				Ok(None) => {}
+				Err(error) => return Err(BuildError { clause: i, error }),
+			}
+		}
+		Ok(unsafe { Self::build_packed(n, lits, &lens) })
+	}
+
+	/// Creates a solver without checking the clauses. Tautologies are dropped
+	/// and the repeated literals are merged as in [`Solver::new`].
+	///
+	/// # Safety
+	///
+	/// Each clause must satisfy the conditions checked by [`Solver::new`].
+	pub unsafe fn new_unchecked(n: usize, clauses: &[Vec<LitInt>]) -> Self {
+		let kept: Vec<_> = clauses
+			.iter()
+			.filter_map(|clause| normalize_clause(n, clause).ok().flatten())
+			.collect();
+		let kept: Vec<_> = kept.iter().map(|clause| &clause[..]).collect();
+		Self::build(n, &kept)
+	}
+
+	/// Creates a solver with the clauses, which are valid and not tautologies.
+	unsafe fn build(n: usize, clauses: &[&[LitInt]]) -> Self {
+		let lits = clauses
+			.iter()
+			.copied()
+			.flatten()
+			.map(|&lit| Lit::new_unchecked(lit))
+			.collect();
+		let lens: Vec<usize> = clauses.iter().map(|clause| clause.len()).collect();
+		Self::build_packed(n, lits, &lens)
+	}
+
+	/// `build()` with the literals of the clauses one after another in
+	/// `lits`, and the length of each clause in `lens`.
+	unsafe fn build_packed(n: usize, lits: Vec<Lit>, lens: &[usize]) -> Self {
+		let clause_body = ClauseBody::new(lits.into_boxed_slice());
+		let base = clause_body.base();
+		let mut units = Vec::new();
+		let mut unsat = false;
+		let mut offset = 0;
+		let clauses = lens
+			.iter()
+			.filter(|&&len| {
+				unsat |= len == 0;
+				len != 0
+			})
+			.map(|&len| {
+				let ptr = (base.add(offset), base.add(offset + len - 1));
+				offset += len;
+				if ptr.0 == ptr.1 {
+					// Unit clauses cannot be watched by two literals.
+					units.push(*ptr.0);
+				}
+				ptr
+			})
+			.collect();
+		let activity = vec![0.0; n + 1];
+		let mut order = VarHeap::new(n);
+		for var in 1..=n {
+			order.insert(var, &activity);
+		}
+		let mut solver = Solver {
+			n,
+			clause_body: vec![clause_body],
+			clauses,
+			learned: Vec::new(),
+			next_reduce: REDUCE_FIRST,
+			watchers: vec![Vec::new(); 2 * (n + 1)],
+			binary: vec![Vec::new(); 2 * (n + 1)],
+			units,
+			unsat,
+			empty: unsat,
+			eliminated: Vec::new(),
+			original: None,
+			assign: Assignment::new(n),
+			levels: vec![0; n + 1],
+			reasons: vec![None; n + 1],
+			suggest: vec![false; n + 1],
+			level: 0,
+			trail: Vec::new(),
+			qhead: 0,
+			out_of_order: false,
+			seen: vec![false; n + 1],
+			frozen: vec![false; n + 1],
+			activity,
+			var_inc: 1.0,
+			var_decay: 0.95,
+			minimization: Minimization::Deep,
+			phase_saving: PhaseSaving::Full,
+			rephase: false,
+			rephases: 0,
+			rephase_countdown: REPHASE_INTERVAL,
+			best_phase: vec![false; n + 1],
+			best_trail: 0,
+			restart_base: 100,
+			restart_strategy: RestartStrategy::Luby,
+			reduce_strategy: ReduceStrategy::Lbd,
+			clause_activity: HashMap::new(),
+			clause_inc: 1.0,
+			clause_decay: 0.999,
+			restarts: 0,
+			restart_conflicts: 0,
+			lbd_fast: 0.0,
+			lbd_slow: 0.0,
+			random_var_freq: 0.0,
+			rng: DEFAULT_SEED,
+			chrono_threshold: None,
+			inprocessing: false,
+			conflicts: 0,
+			decisions: 0,
+			conflict_budget: None,
+			timeout: None,
+			propagations: 0,
+			traversals: 0,
+			relocations: 0,
+			total_restarts: 0,
+			max_trail: 0,
+			total_learned: 0,
+			reductions: 0,
+			deleted_learned: false,
+			order,
+			status: None,
+			assumptions: Vec::new(),
+			assumption_stack: Vec::new(),
+			failed: Vec::new(),
+			core_tracking: false,
+			cores: HashMap::new(),
+			unit_cores: HashMap::new(),
+			final_core: None,
+			proof: None,
+			var_names: HashMap::new(),
+			terminate: None,
+			progress: None,
+			decision: None,
+		};
+		solver.rebuild_watchers();
+		solver
+	}
+
+	/// Adds the clause to the formula.
+	///
+	/// The solver goes back to the level 0 first. A unit clause is assigned
+	/// immediately at the level 0, and the empty clause makes the solver
+	/// unsatisfiable forever. A tautology is ignored, and a repeated literal
+	/// is kept once.
+	pub fn add_clause(&mut self, clause: &[LitInt]) -> Result<(), ClauseError> {
+		if let Some(clause) = normalize_clause(self.n, clause)? {
+			self.keep_added(&clause);
+			self.add_normalized(&clause);
+		}
+		Ok(())
+	}
+
+	/// Adds the clause, which is normalized, without keeping it as an original
+	/// clause.
+	fn add_normalized(&mut self, clause: &[LitInt]) {
+		self.backjump(0);
+		self.status = None;
+		if clause.is_empty() {
+			self.unsat = true;
+			self.empty = true;
+			self.final_core = Some(Vec::new());
+			return;
+		}
+		let body = ClauseBody::new(clause.iter().map(|&lit| Lit::new(lit).unwrap()).collect());
+		let ptr = body.ptr();
+		self.clause_body.push(body);
+		self.attach(ptr);
+	}
+
+	/// Keeps the added clause, which is normalized, with the original clauses
+	/// if they are kept apart from `clauses`.
+	fn keep_added(&mut self, clause: &[LitInt]) {
+		if let Some(original) = &mut self.original {
+			if !clause.is_empty() {
+				original.push(clause.iter().map(|&lit| Lit::new(lit).unwrap()).collect());
+			}
+		}
+	}
+
+	/// Keeps the original clauses apart from `clauses` before a preprocessing
+	/// removes or rewrites some of them for the first time.
+	fn keep_original(&mut self) {
+		if self.original.is_none() {
+			self.original = Some(self.clauses().map(Box::from).collect());
+		}
+	}
+
+	/// The original clauses without the empty clause, see
+	/// [`Solver::to_clauses`].
+	fn original_clauses(&self) -> Box<dyn Iterator<Item = &[Lit]> + '_> {
+		match &self.original {
+			Some(original) => Box::new(original.iter().map(|clause| &clause[..])),
+			None => Box::new(self.clauses()),
+		}
+	}
+
+	/// The number of the original clauses without the empty clause.
+	fn num_original(&self) -> usize {
+		self.original
+			.as_ref()
+			.map_or(self.clauses.len(), |original| original.len())
+	}
+
+	/// Adds the clauses to the formula like [`Solver::add_clause`], with the
+	/// literals stored in one allocation.
+	///
+	/// All the clauses are checked first, so that the solver is not changed
+	/// if one of them is invalid.
+	pub fn add_clauses(&mut self, clauses: &[Vec<LitInt>]) -> Result<(), ClauseError> {
+		let mut kept = Vec::with_capacity(clauses.len());
+		for clause in clauses {
+			if let Some(clause) = normalize_clause(self.n, clause)? {
+				kept.push(clause);
+			}
+		}
+		for clause in &kept {
+			self.keep_added(clause);
+		}
+		let kept: Vec<_> = kept.iter().map(|clause| &clause[..]).collect();
+		self.backjump(0);
+		self.status = None;
+		let body = ClauseBody::new(
+			kept.iter()
+				.copied()
+				.flatten()
+				.map(|&lit| Lit::new(lit).unwrap())
+				.collect(),
+		);
+		let base = body.base();
+		self.clause_body.push(body);
+		self.clauses.reserve(kept.len());
+		let mut offset = 0;
+		for clause in kept {
+			if clause.is_empty() {
+				self.unsat = true;
+				self.empty = true;
+				self.final_core = Some(Vec::new());
+				continue;
+			}
+			let ptr = unsafe { (base.add(offset), base.add(offset + clause.len() - 1)) };
+			offset += clause.len();
+			self.attach(ptr);
+		}
+		Ok(())
+	}
+
+	/// Stores the new clause at `ptr`, which is in a body owned by the solver,
+	/// and watches it at the level 0. The clause is assigned if it is unit.
+	fn attach(&mut self, ptr: ClausePtr) {
+		let body =
+			unsafe { std::slice::from_raw_parts_mut(ptr.0, ptr.1.offset_from(ptr.0) as usize + 1) };
+		// Move the literals which are not false at the level 0 to the watched
+		// ends.
+		body.sort_by_key(|&lit| unsafe { self.eval_unchecked(lit) } == Some(false));
+		let last = body.len() - 1;
+		body.swap(1.min(last), last);
+		self.clauses.push(ptr);
+		let (first, second) = unsafe { (*ptr.0, *ptr.1) };
+		let conflict = if ptr.0 == ptr.1 {
+			self.units.push(first);
+			if self.core_tracking {
+				self.unit_cores.insert(first, Some(vec![ptr]));
+			}
+			match unsafe { self.eval_unchecked(first) } {
+				Some(true) => None,
+				Some(false) => Some(ptr),
+				None => self.assign_and_propagate(first, None),
+			}
+		} else {
+			self.watch(ptr);
+			let unit = unsafe { self.eval_unchecked(second) } == Some(false);
+			match unsafe { self.eval_unchecked(first) } {
+				// All the literals are false.
+				Some(false) => Some(ptr),
+				None if unit => self.assign_and_propagate(first, Some(ptr)),
+				_ => None,
+			}
+		};
+		if let Some(conflict) = conflict {
+			self.unsat = true;
+			self.record_core(&[conflict], &[]);
+		}
+	}
+
+	/// Adds the constraint that the XOR of the literals is `rhs`, i.e. an odd
+	/// number of them are true if `rhs` is `true`, and an even number
+	/// otherwise.
+	///
+	/// The constraint is encoded into the `2^(k-1)` clauses of its `k`
+	/// variables, each of which excludes an assignment of the wrong parity,
+	/// so a long XOR should be split by the caller with fresh variables. A
+	/// pair of `x` and `-x` is allowed and counts as one true literal. The
+	/// XOR of no literals is `false`.
+	pub fn add_xor(&mut self, lits: &[LitInt], mut rhs: bool) -> Result<(), ClauseError> {
+		check_clause(self.n, lits)?;
+		let mut vars = Vec::new();
+		for &lit in lits {
+			// `-x` is `x` XOR `true`.
+			rhs ^= lit < 0;
+			vars.push(lit.abs());
+		}
+		vars.sort_unstable();
+		// `x` XOR `x` is `false`.
+		let mut k = 0;
+		for i in 0..vars.len() {
+			if k > 0 && vars[k - 1] == vars[i] {
+				k -= 1;
+			} else {
+				vars[k] = vars[i];
+				k += 1;
+			}
+		}
+		vars.truncate(k);
+		for bits in 0..1u64 << k {
+			// The assignment where the variable `i` is true iff the bit `i` is
+			// set, which is excluded if its parity is not `rhs`.
+			if (bits.count_ones() % 2 == 1) == rhs {
+				continue;
+			}
+			let clause: Vec<_> = (0..k)
+				.map(|i| {
+					if bits >> i & 1 == 1 {
+						-vars[i]
+					} else {
+						vars[i]
+					}
+				})
+				.collect();
+			self.add_clause(&clause)?;
+		}
+		Ok(())
+	}
+
+	/// Adds the constraint that at most `k` of the literals are true, and
+	/// returns the auxiliary variables allocated for it.
+	///
+	/// The constraint is encoded by the sequential counter, with `k` variables
+	/// for each literal but the last. The auxiliary variable `i * k + j` of the
+	/// result is true if at least `j + 1` of `lits[..=i]` are true. If `k` is
+	/// `0` each literal is made false, and if `k` is at least the number of
+	/// the literals nothing is added.
+	pub fn add_at_most_k(&mut self, lits: &[LitInt], k: usize) -> Result<Vec<LitInt>, ClauseError> {
+		check_clause(self.n, lits)?;
+		let m = lits.len();
+		if k >= m {
+			return Ok(Vec::new());
+		}
+		if k == 0 {
+			for &lit in lits {
+				self.add_clause(&[-lit])?;
+			}
+			return Ok(Vec::new());
+		}
+		let s: Vec<Vec<LitInt>> = (0..m - 1)
+			.map(|_| (0..k).map(|_| self.new_var()).collect())
+			.collect();
+		for &s in &s[0][1..] {
+			self.add_clause(&[-s])?;
+		}
+		for (i, &x) in lits.iter().enumerate() {
+			if i + 1 < m {
+				self.add_clause(&[-x, s[i][0]])?;
+			}
+			if i == 0 {
+				continue;
+			}
+			// The `k + 1`-th true literal.
+			self.add_clause(&[-x, -s[i - 1][k - 1]])?;
+			if i + 1 < m {
+				for j in 0..k {
+					self.add_clause(&[-s[i - 1][j], s[i][j]])?;
+					if j > 0 {
+						self.add_clause(&[-x, -s[i - 1][j - 1], s[i][j]])?;
+					}
+				}
+			}
+		}
+		Ok(s.into_iter().flatten().collect())
+	}
+
+	/// Adds the variable `n + 1`, which appears in no clause yet, and returns
+	/// its positive literal.
+	///
+	/// The assignment and the clauses, including the learned ones, are kept.
+	pub fn new_var(&mut self) -> LitInt {
+		assert!(self.n < LitInt::MAX as usize, "too many variables");
+		self.n += 1;
+		self.watchers.push(Vec::new());
+		self.watchers.push(Vec::new());
+		self.binary.push(Vec::new());
+		self.binary.push(Vec::new());
+		self.assign.grow();
+		self.levels.push(0);
+		self.reasons.push(None);
+		self.suggest.push(false);
+		self.best_phase.push(false);
+		self.seen.push(false);
+		self.frozen.push(false);
+		self.activity.push(0.0);
+		self.order.grow();
+		self.order.insert(self.n, &self.activity);
+		self.n as LitInt
+	}
+
+	/// Clears the assignment and goes back to the level 0, so that the formula
+	/// can be solved again from scratch without allocating a new solver.
+	///
+	/// The clauses, both original and learned, are kept, and each of them is
+	/// watched again by its first and last literal. Learned unit clauses are
+	/// assigned again when the search starts. The activities and the saved
+	/// phases are kept.
+	pub fn reset_assignment(&mut self) {
+		for var in 1..=self.n {
+			self.assign.set(var, None);
+			self.levels[var] = 0;
+			self.reasons[var] = None;
+			self.order.insert(var, &self.activity);
+		}
+		self.trail.clear();
+		self.qhead = 0;
+		self.out_of_order = false;
+		self.level = 0;
+		self.status = None;
+		self.failed.clear();
+		for learned in &self.learned {
+			let (first, last) = learned.ptr;
+			if first == last && !self.units.contains(unsafe { &*first }) {
+				self.units.push(unsafe { *first });
+				if self.core_tracking {
+					let core = self.cores.get(&learned.ptr).cloned().flatten();
+					self.unit_cores.insert(unsafe { *first }, core);
+				}
+			}
+		}
+		self.rebuild_watchers();
+	}
+
+	/// Forgets what the earlier solves learned about the search, so that the
+	/// next one starts cold: the activities of the variables and the learned
+	/// clauses, the saved and the best phases, and the LBD averages of
+	/// [`RestartStrategy::Glucose`] go back to the initial values.
+	///
+	/// The learned clauses themselves are kept, since they follow from the
+	/// formula, and so are the settings and the assignment.
+	pub fn reset_heuristics(&mut self) {
+		self.activity = vec![0.0; self.n + 1];
+		self.var_inc = 1.0;
+		self.order = VarHeap::new(self.n);
+		for var in 1..=self.n {
+			self.order.insert(var, &self.activity);
+		}
+		self.suggest = vec![false; self.n + 1];
+		self.rephases = 0;
+		self.rephase_countdown = REPHASE_INTERVAL;
+		self.best_phase = vec![false; self.n + 1];
+		self.best_trail = 0;
+		self.clause_activity.values_mut().for_each(|a| *a = 0.0);
+		self.clause_inc = 1.0;
+		self.lbd_fast = 0.0;
+		self.lbd_slow = 0.0;
+	}
+
+	/// Watches every clause, original and learned, by its first and last
+	/// literal.
+	fn rebuild_watchers(&mut self) {
+		for watchers in &mut self.watchers {
+			watchers.clear();
+		}
+		for binary in &mut self.binary {
+			binary.clear();
+		}
+		let all: Vec<ClausePtr> = self
+			.clauses
+			.iter()
+			.chain(self.learned.iter().map(|c| &c.ptr))
+			.copied()
+			.filter(|ptr| ptr.0 != ptr.1)
+			.collect();
+		for ptr in all {
+			self.watch(ptr);
+		}
+	}
+
+	/// Watches the clause, which is not a unit one, by its first and last
+	/// literal, in `binary` if they are all.
+	fn watch(&mut self, ptr: ClausePtr) {
+		unsafe {
+			if ptr.0.add(1) == ptr.1 {
+				self.binary[(-*ptr.0).get_loc()].push((*ptr.1, ptr));
+				self.binary[(-*ptr.1).get_loc()].push((*ptr.0, ptr));
+			} else {
+				self.watchers[(-*ptr.0).get_loc()].push(ptr);
+				self.watchers[(-*ptr.1).get_loc()].push(ptr);
+			}
+		}
+	}
+
+	/// Stops watching the clause, which is not a unit one.
+	fn unwatch(&mut self, ptr: ClausePtr) {
+		let binary = unsafe { ptr.0.add(1) } == ptr.1;
+		for &end in &[ptr.0, ptr.1] {
+			let loc = unsafe { (-*end).get_loc() };
+			if binary {
+				let i = self.binary[loc].iter().position(|w| w.1 == ptr).unwrap();
+				self.binary[loc].swap_remove(i);
+			} else {
+				let i = self.watchers[loc].iter().position(|&w| w == ptr).unwrap();
+				self.watchers[loc].swap_remove(i);
+			}
+		}
+	}
+
+	/// Assigns the pure literals, whose negation appears in no clause, at the
+	/// level 0, and removes the clauses containing them.
+	///
+	/// The pure literals are kept as unit clauses. This preserves the
+	/// satisfiability but not the models, so clauses added later and
+	/// assumptions are solved together with these unit clauses.
+	///
+	/// The removed clauses no longer appear in [`Solver::clauses`] and
+	/// [`Solver::learned_clauses`], so the indices change, and the
+	/// [`ClauseRef`]s obtained before are invalidated.
+	pub fn eliminate_pure_literals(&mut self) {
+		self.backjump(0);
+		self.status = None;
+		if !self.simplify() {
+			return;
+		}
+		// Bit 0 for the positive literal, bit 1 for the negative one.
+		let mut polarity = vec![0u8; self.n + 1];
+		for &ptr in self
+			.clauses
+			.iter()
+			.chain(self.learned.iter().map(|c| &c.ptr))
+		{
+			for lit in Self::lits(ptr) {
+				polarity[lit.var()] |= if lit.as_int() > 0 { 1 } else { 2 };
+			}
+		}
+		let mut pure = vec![false; self.n + 1];
+		for var in 1..=self.n {
+			if self.assign[var].is_none()
+				&& !self.frozen[var]
+				&& (polarity[var] == 1 || polarity[var] == 2)
+			{
+				let lit = Lit::new(var as LitInt).unwrap();
+				let lit = if polarity[var] == 1 { lit } else { -lit };
+				pure[var] = true;
+				self.units.push(lit);
+				if self.core_tracking {
+					// A pure literal is not implied, but never used to derive a
+					// conflict either.
+					self.unit_cores.insert(lit, Some(Vec::new()));
+				}
+				self.set(lit, None);
+			}
+		}
+		// No clause has the negation of a pure literal, so nothing is implied.
+		self.qhead = self.trail.len();
+		if pure.contains(&true) {
+			self.keep_original();
+		}
+		let has_pure = |ptr: ClausePtr| Self::lits(ptr).iter().any(|lit| pure[lit.var()]);
+		self.clauses.retain(|&ptr| !has_pure(ptr));
+		let (kept, removed): (Vec<_>, Vec<_>) = std::mem::take(&mut self.learned)
+			.into_iter()
+			.partition(|c| !has_pure(c.ptr));
+		self.learned = kept;
+		// The removed clauses have an unassigned pure literal, so none of them
+		// is a reason.
+		for c in removed {
+			self.log_proof(true, Self::lits(c.ptr));
+			self.cores.remove(&c.ptr);
+			self.clause_activity.remove(&c.ptr);
+		}
+		self.rebuild_watchers();
+	}
+
+	/// Removes the blocked clauses, which have a literal `l` such that every
+	/// resolvent on `l` with the other clauses is a tautology.
+	///
+	/// This preserves the satisfiability, and [`Solver::model`] extends the
+	/// model to the removed clauses by making their blocking literals true.
+	/// It is meant to run before the search: clauses added and assumptions
+	/// given later are solved without the removed clauses, so they should not
+	/// have the variables of the removed clauses. The learned clauses, the
+	/// unit clauses and the reasons at the level 0 are kept.
+	///
+	/// As with [`Solver::eliminate_pure_literals`], the indices of
+	/// [`Solver::clauses`] change, and the [`ClauseRef`]s obtained before are
+	/// invalidated.
+	pub fn eliminate_blocked_clauses(&mut self) {
+		self.backjump(0);
+		self.status = None;
+		if !self.simplify() {
+			return;
+		}
+		let mut occurrences = vec![Vec::new(); 2 * (self.n + 1)];
+		for (i, &ptr) in self.clauses.iter().enumerate() {
+			for lit in Self::lits(ptr) {
+				occurrences[lit.get_loc()].push(i);
+			}
+		}
+		let mut removed = vec![false; self.clauses.len()];
+		let mut marked = vec![false; 2 * (self.n + 1)];
+		// Removing a clause may block others, so repeat until nothing changes.
+		let mut changed = true;
+		while changed {
+			changed = false;
+			for i in 0..self.clauses.len() {
+				let ptr = self.clauses[i];
+				if removed[i] || ptr.0 == ptr.1 || self.is_reason(ptr) {
+					continue;
+				}
+				let lits = Self::lits(ptr);
+				for lit in lits {
+					marked[lit.get_loc()] = true;
+				}
+				// The resolvent on `lit` with `other` is a tautology if `other`
+				// has the negation of another literal.
+				let blocked = |lit: Lit| {
+					occurrences[(-lit).get_loc()]
+						.iter()
+						.filter(|&&j| !removed[j])
+						.all(|&j| {
+							Self::lits(self.clauses[j])
+								.iter()
+								.any(|&l| l != -lit && marked[(-l).get_loc()])
+						})
+				};
+				let blocking = lits
+					.iter()
+					.copied()
+					.find(|&lit| !self.frozen[lit.var()] && blocked(lit));
+				for lit in lits {
+					marked[lit.get_loc()] = false;
+				}
+				if let Some(lit) = blocking {
+					removed[i] = true;
+					self.eliminated.push((lit, lits.to_vec()));
+					changed = true;
+				}
+			}
+		}
+		if removed.contains(&true) {
+			self.keep_original();
+		}
+		let mut i = 0;
+		self.clauses.retain(|_| {
+			i += 1;
+			!removed[i - 1]
+		});
+		self.rebuild_watchers();
+	}
+
+	/// Removes the clauses subsumed by another clause, i.e. having all its
+	/// literals, and returns how many are removed.
+	///
+	/// An original clause subsumes original and learned clauses, and a
+	/// learned clause only learned ones, since it may be deleted later. The
+	/// unit clauses and the reasons at the level 0 are kept. Of the same
+	/// clauses, the first one is kept. As with
+	/// [`Solver::eliminate_blocked_clauses`], the [`ClauseRef`]s obtained
+	/// before are invalidated.
+	pub fn subsume(&mut self) -> usize {
+		self.backjump(0);
+		self.status = None;
+		if !self.simplify() {
+			return 0;
+		}
+		let all: Vec<ClausePtr> = self
+			.clauses
+			.iter()
+			.chain(self.learned.iter().map(|c| &c.ptr))
+			.copied()
+			.collect();
+		let originals = self.clauses.len();
+		// The bit `loc % 64` is set for each literal, so that a clause with a
+		// bit which is not in the other is not its subset.
+		let signatures: Vec<u64> = all
+			.iter()
+			.map(|&ptr| {
+				Self::lits(ptr)
+					.iter()
+					.fold(0, |sig, lit| sig | 1 << (lit.get_loc() % 64))
+			})
+			.collect();
+		let mut occurrences = vec![Vec::new(); 2 * (self.n + 1)];
+		for (i, &ptr) in all.iter().enumerate() {
+			for lit in Self::lits(ptr) {
+				occurrences[lit.get_loc()].push(i);
+			}
+		}
+		let mut removed = vec![false; all.len()];
+		let mut marked = vec![false; 2 * (self.n + 1)];
+		for (i, &ptr) in all.iter().enumerate() {
+			if removed[i] {
+				continue;
+			}
+			let lits = Self::lits(ptr);
+			let rarest = lits
+				.iter()
+				.min_by_key(|lit| occurrences[lit.get_loc()].len())
+				.unwrap();
+			for lit in lits {
+				marked[lit.get_loc()] = true;
+			}
+			for &j in &occurrences[rarest.get_loc()] {
+				let other = Self::lits(all[j]);
+				if removed[j]
+					|| (i >= originals && j < originals)
+					|| other.len() < lits.len()
+					|| (other.len() == lits.len() && j <= i)
+					|| other.len() == 1
+					|| signatures[i] & !signatures[j] != 0
+					|| self.is_reason(all[j])
+				{
+					continue;
+				}
+				if other.iter().filter(|lit| marked[lit.get_loc()]).count() == lits.len() {
+					removed[j] = true;
+				}
+			}
+			for lit in lits {
+				marked[lit.get_loc()] = false;
+			}
+		}
+		let count = removed.iter().filter(|&&r| r).count();
+		self.delete_learned(removed.split_off(originals));
+		if removed.contains(&true) {
+			self.keep_original();
+		}
+		let mut removed = removed.into_iter();
+		self.clauses.retain(|_| !removed.next().unwrap());
+		self.rebuild_watchers();
+		count
+	}
+
+	/// Finds the equivalent literals by the pairs of binary clauses `[-x, y]`
+	/// and `[x, -y]`, and substitutes the representative of each class, the
+	/// literal of its smallest variable, for the others in the clauses.
+	///
+	/// The tautologies made by the substitution are removed, the learned
+	/// clauses with a substituted variable are deleted, and the formula is
+	/// found unsatisfiable if `x` is equivalent to `-x`. [`Solver::model`]
+	/// gives each substituted variable the value of its representative. As
+	/// with [`Solver::eliminate_vars`], it is meant to run before the search,
+	/// clauses added and assumptions given later should not have the
+	/// substituted variables, and the [`ClauseRef`]s obtained before are
+	/// invalidated.
+	pub fn detect_equivalences(&mut self) {
+		self.backjump(0);
+		self.status = None;
+		if !self.simplify() {
+			return;
+		}
+		let unassigned = |lits: &[Lit]| {
+			lits.iter()
+				.all(|&lit| unsafe { self.eval_unchecked(lit) }.is_none())
+		};
+		let binary: HashSet<(Lit, Lit)> = self
+			.clauses
+			.iter()
+			.map(|&ptr| Self::lits(ptr))
+			.filter(|lits| lits.len() == 2 && unassigned(lits))
+			.map(|lits| (lits[0].min(lits[1]), lits[0].max(lits[1])))
+			.collect();
+		// `parent[var]` is a literal equivalent to `var`, which is `var` itself
+		// for the representatives.
+		let mut parent: Vec<Lit> = (0..=self.n as LitInt)
+			.map(|var| Lit::new(var.max(1)).unwrap())
+			.collect();
+		let mut pairs: Vec<_> = binary.iter().copied().collect();
+		// The order of a hash set is not fixed.
+		pairs.sort_unstable();
+		for (p, q) in pairs {
+			if !binary.contains(&((-p).min(-q), (-p).max(-q))) {
+				continue;
+			}
+			// `p | q` and `-p | -q` mean `p = -q`.
+			let (a, b) = (find_root(&mut parent, p), find_root(&mut parent, -q));
+			if a == -b {
+				self.unsat = true;
+				self.final_core = None;
+				return;
+			}
+			let (a, b) = if a.var() < b.var() { (a, b) } else { (b, a) };
+			if a != b {
+				parent[b.var()] = if b.as_int() > 0 { a } else { -a };
+			}
+		}
+		let mut substituted = vec![false; self.n + 1];
+		for (var, substituted) in substituted.iter_mut().enumerate().skip(1) {
+			let x = Lit::new(var as LitInt).unwrap();
+			let r = find_root(&mut parent, x);
+			if r != x {
+				*substituted = true;
+				self.eliminated.push((x, vec![x, -r]));
+				self.eliminated.push((-x, vec![-x, r]));
+			}
+		}
+		let has_substituted =
+			|ptr: ClausePtr| Self::lits(ptr).iter().any(|lit| substituted[lit.var()]);
+		let (changed, kept): (Vec<ClausePtr>, Vec<ClausePtr>) =
+			self.clauses.iter().partition(|&&ptr| has_substituted(ptr));
+		let delete = self
+			.learned
+			.iter()
+			.map(|c| has_substituted(c.ptr))
+			.collect();
+		if !changed.is_empty() {
+			self.keep_original();
+		}
+		self.clauses = kept;
+		self.delete_learned(delete);
+		self.rebuild_watchers();
+		for ptr in changed {
+			let mut lits: Vec<Lit> = Self::lits(ptr)
+				.iter()
+				.map(|&lit| find_root(&mut parent, lit))
+				.collect();
+			lits.sort_unstable_by_key(|lit| (lit.var(), lit.as_int()));
+			lits.dedup();
+			if lits.windows(2).any(|w| w[0] == -w[1]) {
+				continue;
+			}
+			// The literals are rewritten in place, leaving the rest of the body
+			// unused.
+			let ptr = unsafe {
+				std::ptr::copy_nonoverlapping(lits.as_ptr(), ptr.0, lits.len());
+				(ptr.0, ptr.0.add(lits.len() - 1))
+			};
+			self.attach(ptr);
+		}
+	}
+
+	/// Probes each unassigned variable at the level 0: assigns each of its
+	/// literals at the level 1 and propagates it, and if it leads to a
+	/// conflict, its negation is kept as a unit clause and propagated at the
+	/// level 0. Returns how many literals are newly assigned at the level 0.
+	///
+	/// The formula is found unsatisfiable if both literals of a variable lead
+	/// to a conflict.
+	pub fn probe(&mut self) -> usize {
+		self.backjump(0);
+		self.status = None;
+		if !self.simplify() {
+			return 0;
+		}
+		let start = self.trail.len();
+		for var in 1..=self.n {
+			let x = Lit::new(var as LitInt).unwrap();
+			for &lit in &[x, -x] {
+				if self.assign[var].is_some() {
+					break;
+				}
+				self.level = 1;
+				let conflict = self.assign_and_propagate(lit, None);
+				let core = match conflict {
+					Some(ptr) if self.core_tracking => Some(self.trace(&[ptr], &[], &[])),
+					_ => None,
+				};
+				self.backjump(0);
+				if conflict.is_some() {
+					self.units.push(-lit);
+					if let Some(core) = core {
+						self.unit_cores.insert(-lit, core);
+					}
+					self.log_proof(false, &[-lit]);
+					if !self.simplify() {
+						return self.trail.len() - start;
+					}
+				}
+			}
+		}
+		self.trail.len() - start
+	}
+
+	/// Eliminates the variables by resolution: the clauses with a variable
+	/// `x` are replaced by their non-tautological resolvents on `x`, if they
+	/// are not more than the replaced clauses plus `grow_limit`.
+	///
+	/// This preserves the satisfiability, and [`Solver::model`] gives values
+	/// to the eliminated variables by the replaced clauses. The learned
+	/// clauses with an eliminated variable are deleted. As with
+	/// [`Solver::eliminate_blocked_clauses`], it is meant to run before the
+	/// search, clauses added and assumptions given later should not have the
+	/// eliminated variables, and the [`ClauseRef`]s obtained before are
+	/// invalidated.
+	pub fn eliminate_vars(&mut self, grow_limit: usize) {
+		self.backjump(0);
+		self.status = None;
+		if !self.simplify() {
+			return;
+		}
+		for var in 1..=self.n {
+			if self.unsat {
+				return;
+			}
+			if self.assign[var].is_some() || self.frozen[var] {
+				continue;
+			}
+			let x = Lit::new(var as LitInt).unwrap();
+			let (pos, neg): (Vec<_>, Vec<_>) = self
+				.clauses
+				.iter()
+				.map(|&ptr| Self::lits(ptr))
+				.filter(|lits| lits.iter().any(|l| l.var() == var))
+				.partition(|lits| lits.contains(&x));
+			if pos.is_empty() && neg.is_empty() {
+				continue;
+			}
+			let limit = pos.len() + neg.len() + grow_limit;
+			let mut resolvents = Vec::new();
+			'pos: for p in &pos {
+				for n in &neg {
+					let mut resolvent: Vec<LitInt> = p
+						.iter()
+						.chain(n.iter())
+						.filter(|l| l.var() != var)
+						.map(|l| l.get())
+						.collect();
+					resolvent.sort_unstable_by_key(|lit| (lit.unsigned_abs(), *lit));
+					resolvent.dedup();
+					if resolvent.windows(2).any(|w| w[0] == -w[1]) {
+						continue;
+					}
+					resolvents.push(resolvent);
+					if resolvents.len() > limit {
+						break 'pos;
+					}
+				}
+			}
+			if resolvents.len() > limit {
+				continue;
+			}
+			// `x` is true if a clause of `pos` is false, which is found first
+			// in the reverse order, and false if a clause of `neg` is false.
+			for lits in neg.iter() {
+				self.eliminated.push((-x, lits.to_vec()));
+			}
+			for lits in pos.iter() {
+				self.eliminated.push((x, lits.to_vec()));
+			}
+			self.keep_original();
+			let has_var = |ptr: ClausePtr| Self::lits(ptr).iter().any(|l| l.var() == var);
+			self.clauses.retain(|&ptr| !has_var(ptr));
+			let (kept, removed): (Vec<_>, Vec<_>) = std::mem::take(&mut self.learned)
+				.into_iter()
+				.partition(|c| !has_var(c.ptr));
+			self.learned = kept;
+			for c in removed {
+				self.log_proof(true, Self::lits(c.ptr));
+				self.cores.remove(&c.ptr);
+				self.clause_activity.remove(&c.ptr);
+			}
+			self.rebuild_watchers();
+			for resolvent in resolvents {
+				// The resolvent is normalized above, and not an original clause.
+				self.add_normalized(&resolvent);
+			}
+		}
+	}
+
+	/// Keeps the variable from the eliminations, so that it can be used in
+	/// the clauses added and the assumptions given later:
+	/// [`Solver::eliminate_vars`] does not eliminate it,
+	/// [`Solver::eliminate_pure_literals`] does not assign it, and
+	/// [`Solver::eliminate_blocked_clauses`] does not remove a clause blocked
+	/// by its literal. The other simplifications still assign it at the
+	/// level 0 if it is implied.
+	///
+	/// # Panics
+	///
+	/// Panics if the variable is out of `1..=n`.
+	pub fn freeze(&mut self, var: usize) {
+		assert!(var >= 1 && var <= self.n, "bad variable {}", var);
+		self.frozen[var] = true;
+	}
+
+	/// Allows the eliminations of the variable again, undoing
+	/// [`Solver::freeze`].
+	///
+	/// # Panics
+	///
+	/// Panics if the variable is out of `1..=n`.
+	pub fn melt(&mut self, var: usize) {
+		assert!(var >= 1 && var <= self.n, "bad variable {}", var);
+		self.frozen[var] = false;
+	}
+
+	/// The number of variables.
+	pub fn num_vars(&self) -> usize {
+		self.n
+	}
+
+	/// The number of clauses, both original and learned.
+	///
+	/// Learned clauses already deleted are not counted, neither are empty
+	/// clauses, which are not stored but make the solver unsatisfiable.
+	pub fn num_clauses(&self) -> usize {
+		self.clauses.len() + self.learned.len()
+	}
+
+	/// The number of learned clauses currently kept.
+	pub fn num_learned(&self) -> usize {
+		self.learned.len()
+	}
+
+	/// Iterates over the original clauses, those given to `new` and then
+	/// added by `add_clause`, as they are after the preprocessing: the
+	/// eliminations, [`Solver::subsume`] and [`Solver::detect_equivalences`]
+	/// remove or rewrite some of them, and [`Solver::eliminate_vars`] adds
+	/// the resolvents.
+	///
+	/// The order of the literals in each clause may differ from the input,
+	/// because propagation moves the watched literals.
+	pub fn clauses(&self) -> impl Iterator<Item = &[Lit]> + '_ {
+		self.clauses.iter().map(|&ptr| Self::lits(ptr))
+	}
+
+	/// The original clauses as integers, followed by the empty clause if one
+	/// is given. Passing them to [`Solver::new`] gives a solver with the same
+	/// formula.
+	///
+	/// They are in the order of [`Solver::clauses`] until a preprocessing
+	/// changes it, and are then kept as they were before it, with the
+	/// clauses added later.
+	pub fn to_clauses(&self) -> Vec<Vec<LitInt>> {
+		let mut clauses: Vec<Vec<LitInt>> = self
+			.original_clauses()
+			.map(|clause| clause.iter().map(|lit| lit.get()).collect())
+			.collect();
+		if self.empty {
+			clauses.push(Vec::new());
+		}
+		clauses
+	}
+
+	/// The number of variables and the original clauses as integers, as
+	/// [`Solver::to_clauses`].
+	pub fn into_clauses(self) -> (usize, Vec<Vec<LitInt>>) {
+		(self.n, self.to_clauses())
+	}
+
+	/// The `i`-th original clause.
+	///
+	/// # Panics
+	///
+	/// Panics if `i >= self.num_clauses()`.
+	pub fn clause_ref(&self, i: usize) -> ClauseRef {
+		assert!(i < self.clauses.len(), "clause {} out of range", i);
+		ClauseRef {
+			learned: false,
+			index: i,
+		}
+	}
+
+	/// The literals of the clause, in the same order as [`Solver::clauses`].
+	///
+	/// # Panics
+	///
+	/// Panics if the clause is removed.
+	pub fn clause_lits(&self, clause: ClauseRef) -> &[Lit] {
+		Self::lits(self.ptr_of(clause))
+	}
+
+	/// The clause which implied the variable, or `None` if it is unassigned or
+	/// decided.
+	///
+	/// # Panics
+	///
+	/// Panics if the variable is out of `1..=n`.
+	pub fn reason_of(&self, var: usize) -> Option<ClauseRef> {
+		assert!(var >= 1 && var <= self.n, "bad variable {}", var);
+		self.reasons[var].map(|ptr| self.ref_of(ptr))
+	}
+
+	/// The two watched literals of the clause, which are the first and the
+	/// last one of [`Solver::clause_lits`]. They are the same for a unit
+	/// clause, which is not watched.
+	///
+	/// # Panics
+	///
+	/// Panics if the clause is removed.
+	pub fn watched(&self, clause: ClauseRef) -> (Lit, Lit) {
+		let lits = self.clause_lits(clause);
+		(lits[0], lits[lits.len() - 1])
+	}
+
+	/// The number of distinct decision levels among the assigned literals of
+	/// the clause, the literal block distance used by [`ReduceStrategy::Lbd`].
+	/// Unassigned literals are not counted, so it is only meaningful when
+	/// most of the literals are assigned, as they are for a learned clause
+	/// when it is derived.
+	///
+	/// # Panics
+	///
+	/// Panics if the clause is removed.
+	pub fn lbd_of(&self, clause: ClauseRef) -> usize {
+		let lits: Vec<Lit> = self
+			.clause_lits(clause)
+			.iter()
+			.copied()
+			.filter(|&lit| unsafe { self.eval_unchecked(lit) }.is_some())
+			.collect();
+		self.lbd(&lits)
+	}
+
+	/// The clauses watching the literal, which are visited when it becomes
+	/// false.
+	///
+	/// # Panics
+	///
+	/// Panics if the literal is zero or out of `-n..=n`.
+	pub fn watchers_of(&self, lit: LitInt) -> Vec<ClauseRef> {
+		assert!(
+			lit != 0 && lit.unsigned_abs() as usize <= self.n,
+			"bad literal {}",
+			lit
+		);
+		let loc = (-Lit::new(lit).unwrap()).get_loc();
+		self.binary[loc]
+			.iter()
+			.map(|&(_, ptr)| ptr)
+			.chain(self.watchers[loc].iter().copied())
+			.map(|ptr| self.ref_of(ptr))
+			.collect()
+	}
+
+	/// Checks the invariants which the unsafe code relies on, and describes
+	/// the first broken one:
+	///
+	/// - each clause points into a body owned by the solver,
+	/// - the watchers hold exactly the first and the last literal of each
+	///   clause but the unit ones, those of the binary clauses in the
+	///   separate lists with the other literal,
+	/// - a false watched literal has the other one true, unless propagation is
+	///   pending, a clause is falsified, or the formula is found
+	///   unsatisfiable,
+	/// - the trail and the assignment agree, with levels up to the current
+	///   one, which are non-decreasing unless backtracked chronologically,
+	///   and each reason implies its literal.
+	///
+	/// It takes time linear in the size of the formula, and is checked after
+	/// each solve in debug builds. With the `debug-invariants` feature, it is
+	/// also checked after each propagated literal and each backjump, so that
+	/// a violation is caught at the step which makes it. There the third one
+	/// is weakened to the literals already propagated, and a clause with a
+	/// false watched literal may also be unit, as the learned clause before
+	/// its literal is assigned.
+	pub fn verify_watch_invariant(&self) -> Result<(), String> {
+		self.verify_invariant(false)
+	}
+
+	/// `verify_watch_invariant()`, or its weaker form between the steps of
+	/// the search if `stepwise` is set.
+	fn verify_invariant(&self, stepwise: bool) -> Result<(), String> {
+		let ints =
+			|ptr: ClausePtr| -> Vec<LitInt> { Self::lits(ptr).iter().map(|l| l.get()).collect() };
+		let range = |body: &ClauseBody| body.base()..body.base().wrapping_add(body.0.len());
+		let within = |ptr: ClausePtr, range: &std::ops::Range<*mut Lit>| {
+			ptr.0 <= ptr.1 && range.contains(&ptr.0) && range.contains(&ptr.1)
+		};
+		// The bodies do not move during the search, so they are checked only
+		// by the full check.
+		if !stepwise {
+			// Sorted by the addresses, to find the body of each clause.
+			let mut ranges: Vec<_> = self.clause_body.iter().map(range).collect();
+			ranges.sort_unstable_by_key(|range| range.start);
+			for &ptr in &self.clauses {
+				let i = ranges.partition_point(|range| range.start <= ptr.0);
+				if i == 0 || !within(ptr, &ranges[i - 1]) {
+					return Err(format!(
+						"clause {:?} is out of the clause bodies",
+						ints(ptr)
+					));
+				}
+			}
+			for c in &self.learned {
+				if !within(c.ptr, &range(&c.body)) {
+					return Err(format!(
+						"learned clause {:?} is out of its body",
+						ints(c.ptr)
+					));
+				}
+			}
+		}
+		let all: Vec<ClausePtr> = self
+			.clauses
+			.iter()
+			.chain(self.learned.iter().map(|c| &c.ptr))
+			.copied()
+			.filter(|ptr| ptr.0 != ptr.1)
+			.collect();
+		// The binary watches are keyed with the other literal.
+		let count = self.watchers.iter().map(Vec::len).sum::<usize>()
+			+ self.binary.iter().map(Vec::len).sum::<usize>();
+		let mut watches: HashMap<_, _, BuildHasherDefault<FastHasher>> =
+			HashMap::with_capacity_and_hasher(count, Default::default());
+		for (loc, watchers) in self.watchers.iter().enumerate() {
+			for &ptr in watchers {
+				*watches.entry((loc, ptr, None)).or_insert(0) += 1;
+			}
+		}
+		for (loc, binary) in self.binary.iter().enumerate() {
+			for &(other, ptr) in binary {
+				*watches.entry((loc, ptr, Some(other))).or_insert(0) += 1;
+			}
+		}
+		for &ptr in &all {
+			let (first, last) = unsafe { (*ptr.0, *ptr.1) };
+			let binary = Self::lits(ptr).len() == 2;
+			for (lit, other) in [(first, last), (last, first)] {
+				let key = ((-lit).get_loc(), ptr, Some(other).filter(|_| binary));
+				if watches.get(&key) != Some(&1) {
+					return Err(format!(
+						"clause {:?} is not watched once by {}",
+						ints(ptr),
+						lit
+					));
+				}
+			}
+		}
+		if count != 2 * all.len() {
+			return Err(format!("{} watches for {} clauses", count, all.len()));
+		}
+		let falsified = |ptr: ClausePtr| {
+			Self::lits(ptr)
+				.iter()
+				.all(|&lit| unsafe { self.eval_unchecked(lit) } == Some(false))
+		};
+		// Only the false watched literals which are propagated are checked,
+		// all or none of them unless `stepwise`.
+		let propagated = if stepwise {
+			let mut propagated = vec![false; self.n + 1];
+			for lit in &self.trail[..self.qhead] {
+				propagated[lit.var()] = true;
+			}
+			propagated
+		} else {
+			vec![self.qhead == self.trail.len(); self.n + 1]
+		};
+		if !self.unsat && !all.iter().any(|&ptr| falsified(ptr)) {
+			for &ptr in &all {
+				let (first, last) = unsafe { (*ptr.0, *ptr.1) };
+				let value = |lit| unsafe { self.eval_unchecked(lit) };
+				let unit = |other| {
+					Self::lits(ptr)
+						.iter()
+						.all(|&l| l == other || value(l) == Some(false))
+				};
+				for (lit, other) in [(first, last), (last, first)] {
+					if value(lit) == Some(false)
+						&& propagated[lit.var()]
+						&& value(other) != Some(true)
+						&& !(stepwise && unit(other))
+					{
+						return Err(format!(
+							"clause {:?} watches false {} with {} not true",
+							ints(ptr),
+							lit,
+							other
+						));
+					}
+				}
+			}
+		}
+		let mut level = 0;
+		for &lit in &self.trail {
+			let var = lit.var();
+			if self.assign[var] != Some(lit.get() > 0) {
+				return Err(format!("{} is on the trail but not assigned", lit));
+			}
+			if (self.levels[var] < level && !self.out_of_order) || self.levels[var] > self.level {
+				return Err(format!(
+					"{} has the level {} out of order",
+					lit, self.levels[var]
+				));
+			}
+			level = self.levels[var];
+			if let Some(reason) = self.reasons[var] {
+				let implied = Self::lits(reason)
+					.iter()
+					.all(|&l| l == lit || unsafe { self.eval_unchecked(l) } == Some(false));
+				if !Self::lits(reason).contains(&lit) || !implied {
+					return Err(format!("{} is not implied by {:?}", lit, ints(reason)));
+				}
+			}
+		}
+		let assigned = self.assign.iter().filter(|v| v.is_some()).count();
+		if assigned != self.trail.len() {
+			return Err(format!(
+				"{} variables assigned but {} on the trail",
+				assigned,
+				self.trail.len()
+			));
+		}
+		Ok(())
+	}
+
+	fn ptr_of(&self, clause: ClauseRef) -> ClausePtr {
+		let ptr = if clause.learned {
+			self.learned.get(clause.index).map(|c| c.ptr)
+		} else {
+			self.clauses.get(clause.index).copied()
+		};
+		ptr.expect("removed clause")
+	}
+
+	/// Finds the clause by a linear search, which is fine for inspection.
+	fn ref_of(&self, ptr: ClausePtr) -> ClauseRef {
+		match self.clauses.iter().position(|&p| p == ptr) {
+			Some(index) => ClauseRef {
+				learned: false,
+				index,
+			},
+			None => ClauseRef {
+				learned: true,
+				index: self.learned.iter().position(|c| c.ptr == ptr).unwrap(),
+			},
+		}
+	}
+
+	/// Iterates over the learned clauses currently kept.
+	pub fn learned_clauses(&self) -> impl Iterator<Item = &[Lit]> + '_ {
+		self.learned.iter().map(|c| Self::lits(c.ptr))
+	}
+
+	/// Evaluates the literal under the current assignment.
+	///
+	/// # Safety
+	///
+	/// The variable of `lit` must be in `1..=n`.
+	#[inline]
+	unsafe fn eval_unchecked(&self, lit: Lit) -> Option<bool> {
+		self.assign
+			.get_unchecked(lit.var())
+			.map(|b| b == (lit.as_int() > 0))
+	}
+
+	/// Visits the clauses watching `-lit`, where `lit` has just become true.
+	///
+	/// The literals implied by the binary clauses are assigned first, without
+	/// touching the watchers. Then the watches are moved to other non-false
+	/// literals where possible. The literals implied by the longer clauses
+	/// which became unit are pushed to `later_assigns` with the implying
+	/// clause, but not assigned.
+	fn propagate_once(
+		&mut self,
+		lit: Lit,
+		later_assigns: &mut Vec<(Lit, ClausePtr)>,
+	) -> PropagateResult {
+		self.traversals += 1;
+		let loc = lit.get_loc();
+		let mut i = 0;
+		while i < self.binary[loc].len() {
+			let (other, ptr) = self.binary[loc][i];
+			match unsafe { self.eval_unchecked(other) } {
+				Some(true) => {}
+				Some(false) => return PropagateResult::Conflict(ptr),
+				None => self.set(other, Some(ptr)),
+			}
+			i += 1;
+		}
+		let false_lit = -lit;
+		i = 0;
+		'clauses: while i < self.watchers[loc].len() {
+			let ptr = self.watchers[loc][i];
+			unsafe {
+				// Make `ptr.0` point the false watched literal.
+				let (false_pos, other_pos) = if *ptr.0 == false_lit {
+					(ptr.0, ptr.1)
+				} else {
+					(ptr.1, ptr.0)
+				};
+				let other = *other_pos;
+				if self.eval_unchecked(other) == Some(true) {
+					i += 1;
+					continue;
+				}
+				let mut p = ptr.0.add(1);
+				while p < ptr.1 {
+					if self.eval_unchecked(*p) != Some(false) {
+						std::ptr::swap(p, false_pos);
+						self.watchers[loc].swap_remove(i);
+						self.watchers[(-*false_pos).get_loc()].push(ptr);
+						self.relocations += 1;
+						continue 'clauses;
+					}
+					p = p.add(1);
+				}
+				if self.eval_unchecked(other).is_some() {
+					return PropagateResult::Conflict(ptr);
+				}
+				later_assigns.push((other, ptr));
+				if self.chrono_threshold.is_some() && self.watch_highest(ptr, false_pos) {
+					continue 'clauses;
+				}
+			}
+			i += 1;
+		}
+		PropagateResult::Ok
+	}
+
+	/// Swaps the false literal at `pos`, an end of the clause, with the one
+	/// assigned at the highest level among the literals between the ends if
+	/// it is higher, moving the watch. Returns whether they are swapped.
+	///
+	/// Without chronological backtracking, the literal propagated last is
+	/// always at the highest level. Otherwise a clause could keep watching
+	/// a false literal after backjumping.
+	unsafe fn watch_highest(&mut self, ptr: ClausePtr, pos: *mut Lit) -> bool {
+		let mut highest = pos;
+		let mut p = ptr.0.add(1);
+		while p < ptr.1 {
+			if self.levels[(*p).var()] > self.levels[(*highest).var()] {
+				highest = p;
+			}
+			p = p.add(1);
+		}
+		if highest == pos {
+			return false;
+		}
+		let watchers = &mut self.watchers[(-*pos).get_loc()];
+		let i = watchers.iter().position(|&c| c == ptr).unwrap();
+		watchers.swap_remove(i);
+		std::ptr::swap(highest, pos);
+		self.watchers[(-*pos).get_loc()].push(ptr);
+		self.relocations += 1;
+		true
+	}
+
+	/// Assigns `lit` by `set` and propagates it to fixpoint.
+	///
+	/// Returns the falsified clause on conflict.
+	fn assign_and_propagate(&mut self, lit: Lit, reason: Option<ClausePtr>) -> Option<ClausePtr> {
+		self.set(lit, reason);
+		self.propagate()
+	}
+
+	/// Propagates the literals on the trail which are not propagated yet,
+	/// assigning the implied literals with their reasons, until nothing is
+	/// left or a conflict is found.
+	///
+	/// Returns the falsified clause on conflict. The rest of the trail is then
+	/// left unpropagated, which is fine because a conflict is always followed
+	/// by a backjump.
+	fn propagate(&mut self) -> Option<ClausePtr> {
+		let mut later_assigns = Vec::new();
+		while let Some(&lit) = self.trail.get(self.qhead) {
+			self.qhead += 1;
+			let mut conflict = match self.propagate_once(lit, &mut later_assigns) {
+				PropagateResult::Conflict(ptr) => Some(ptr),
+				PropagateResult::Ok => None,
+			};
+			for (lit, ptr) in later_assigns.drain(..) {
+				match unsafe { self.eval_unchecked(lit) } {
+					Some(true) => {}
+					// All the literals in `ptr` are false now.
+					Some(false) => conflict = conflict.or(Some(ptr)),
+					None if conflict.is_none() => self.set(lit, Some(ptr)),
+					None => {}
+				}
+			}
+			if conflict.is_some() {
+				self.qhead = self.trail.len();
+				self.check_invariant();
+				return conflict;
+			}
+			self.check_invariant();
+		}
+		None
+	}
+
+	/// Assigns the literal at the current level, or with chronological
+	/// backtracking at the highest level among the other literals of its
+	/// reason.
+	#[inline]
+	fn set(&mut self, lit: Lit, reason: Option<ClausePtr>) {
+		let level = match reason {
+			Some(ptr) if self.chrono_threshold.is_some() => Self::lits(ptr)
+				.iter()
+				.filter(|l| l.var() != lit.var())
+				.map(|l| self.levels[l.var()])
+				.max()
+				.unwrap_or(0),
+			_ => self.level,
+		};
+		self.out_of_order |= level < self.level;
+		self.assign.set(lit.var(), Some(lit.as_int() > 0));
+		self.levels[lit.var()] = level;
+		self.reasons[lit.var()] = reason;
+		self.trail.push(lit);
+		self.max_trail = self.max_trail.max(self.trail.len());
+		self.propagations += 1;
+	}
+
+	/// Unassigns all the variables assigned at levels above `level`, and makes
+	/// `level` the current level.
+	///
+	/// Watches need no update, because they only move on propagation and a
+	/// watched literal never becomes false by unassigning.
+	///
+	/// If the trail is out of order, the literals at the lower levels after
+	/// the first unassigned one are kept in order, and propagated again to
+	/// find the implications lost with the unassigned literals.
+	fn backjump(&mut self, level: usize) {
+		if self.out_of_order {
+			let mut kept = 0;
+			let mut first = None;
+			for i in 0..self.trail.len() {
+				let lit = self.trail[i];
+				if self.levels[lit.var()] <= level {
+					self.trail[kept] = lit;
+					kept += 1;
+				} else {
+					first = first.or(Some(kept));
+					self.unassign(lit);
+				}
+			}
+			self.trail.truncate(kept);
+			self.qhead = self.qhead.min(first.unwrap_or(kept));
+			self.out_of_order = level > 0;
+			self.level = level;
+			self.check_invariant();
+			return;
+		}
+		while let Some(&lit) = self.trail.last() {
+			if self.levels[lit.var()] <= level {
+				break;
+			}
+			self.unassign(lit);
+			self.trail.pop();
+		}
+		// The literals at the lower levels have been propagated, unless left
+		// by an out of order backjump.
+		self.qhead = self.qhead.min(self.trail.len());
+		self.level = level;
+		self.check_invariant();
+	}
+
+	/// Panics if [`Solver::verify_watch_invariant`] fails, with the
+	/// `debug-invariants` feature. Otherwise it does nothing.
+	#[inline]
+	fn check_invariant(&self) {
+		#[cfg(feature = "debug-invariants")]
+		if let Err(e) = self.verify_invariant(true) {
+			panic!("broken invariant: {}", e);
+		}
+	}
+
+	/// Unassigns the literal, which is removed from the trail by the caller
+	/// before `level` is lowered.
+	fn unassign(&mut self, lit: Lit) {
+		let save = match self.phase_saving {
+			PhaseSaving::None => false,
+			PhaseSaving::Limited => self.levels[lit.var()] == self.level,
+			PhaseSaving::Full => true,
+		};
+		if save {
+			self.suggest[lit.var()] = lit.as_int() > 0;
+		}
+		self.assign.set(lit.var(), None);
+		self.reasons[lit.var()] = None;
+		self.order.insert(lit.var(), &self.activity);
+	}
+
+	/// The literals of the clause.
+	#[inline]
+	fn lits<'a>(ptr: ClausePtr) -> &'a [Lit] {
+		unsafe { std::slice::from_raw_parts(ptr.0, ptr.1.offset_from(ptr.0) as usize + 1) }
+	}
+
+	/// Derives the first UIP clause from the conflicting clause, by resolving
+	/// it with the reasons of the literals assigned at the current level.
+	///
+	/// Returns the learned clause, whose first literal is the negation of the
+	/// UIP, and the level to jump back, which is the highest level among the
+	/// other literals (0 if there are none).
+	fn analyze(&mut self, conflict: ClausePtr) -> (Vec<Lit>, usize) {
+		let mut learned = Vec::new();
+		let mut count = 0;
+		let mut index = self.trail.len();
+		let mut clause = conflict;
+		let mut resolved = None;
+		loop {
+			if self.reduce_strategy == ReduceStrategy::Activity {
+				self.bump_clause(clause);
+			}
+			for &lit in Self::lits(clause) {
+				let var = lit.var();
+				if Some(lit) == resolved || self.seen[var] || self.levels[var] == 0 {
+					continue;
+				}
+				self.seen[var] = true;
+				if self.levels[var] == self.level {
+					count += 1;
+				} else {
+					learned.push(lit);
+				}
+			}
+			let lit = loop {
+				index -= 1;
+				let lit = self.trail[index];
+				// The lower levels may follow on an out of order trail.
+				if self.seen[lit.var()] && self.levels[lit.var()] == self.level {
+					break lit;
+				}
+			};
+			self.seen[lit.var()] = false;
+			count -= 1;
+			if count == 0 {
+				learned.insert(0, -lit);
+				break;
+			}
+			resolved = Some(lit);
+			clause = self.reasons[lit.var()].unwrap();
+		}
+		self.minimize(&mut learned);
+		for lit in &learned {
+			let var = lit.var();
+			self.seen[var] = false;
+			self.bump(var);
+		}
+		let level = learned[1..]
+			.iter()
+			.map(|lit| self.levels[lit.var()])
+			.max()
+			.unwrap_or(0);
+		(learned, level)
+	}
+
+	/// Removes the literals implied by the others from the clause learned by
+	/// `analyze`, whose variables are marked in `seen`. The first literal is
+	/// kept.
+	///
+	/// The marks of the removed literals are cleared.
+	fn minimize(&mut self, learned: &mut Vec<Lit>) {
+		let mut marked = Vec::new();
+		let mut j = 1;
+		for i in 1..learned.len() {
+			let lit = learned[i];
+			let redundant = match self.minimization {
+				Minimization::None => false,
+				Minimization::Basic => self.reasons[lit.var()].is_some_and(|reason| {
+					Self::lits(reason)
+						.iter()
+						.all(|&l| l == -lit || self.seen[l.var()] || self.levels[l.var()] == 0)
+				}),
+				Minimization::Deep => self.redundant(lit, &mut marked),
+			};
+			if redundant {
+				marked.push(lit.var());
+			} else {
+				learned[j] = lit;
+				j += 1;
+			}
+		}
+		learned.truncate(j);
+		for var in marked {
+			self.seen[var] = false;
+		}
+	}
+
+	/// Whether the literal of the learned clause is implied by the others,
+	/// i.e. every path from it in the implication graph ends in the clause or
+	/// at the level 0.
+	///
+	/// The variables on the paths are marked in `seen` and pushed to `marked`
+	/// on success, so that they are not searched again. On failure the marks
+	/// of this call are cleared. The search uses an explicit stack, since the
+	/// graph may be deep.
+	fn redundant(&mut self, lit: Lit, marked: &mut Vec<usize>) -> bool {
+		let top = marked.len();
+		let mut stack = match self.reasons[lit.var()] {
+			Some(reason) => vec![reason],
+			None => return false,
+		};
+		while let Some(clause) = stack.pop() {
+			for &lit in Self::lits(clause) {
+				let var = lit.var();
+				if self.seen[var] || self.levels[var] == 0 {
+					continue;
+				}
+				match self.reasons[var] {
+					Some(reason) => {
+						self.seen[var] = true;
+						marked.push(var);
+						stack.push(reason);
+					}
+					None => {
+						for var in marked.drain(top..) {
+							self.seen[var] = false;
+						}
+						return false;
+					}
+				}
+			}
+		}
+		true
+	}
+
+	/// Stores the clause learned by `analyze` and watches it.
+	///
+	/// The first literal is kept, and the literal assigned at the highest
+	/// level among the others is moved to the end, so that the clause is
+	/// watched by the two literals unassigned last by backjumping.
+	fn learn(&mut self, mut lits: Vec<Lit>) -> ClausePtr {
+		let last = lits.len() - 1;
+		if let Some(i) = (1..lits.len()).max_by_key(|&i| self.levels[lits[i].var()]) {
+			lits.swap(i, last);
+		}
+		let lbd = self.lbd(&lits);
+		if self.lbd_slow == 0.0 {
+			self.lbd_fast = lbd as f64;
+			self.lbd_slow = lbd as f64;
+		}
+		self.lbd_fast += LBD_FAST_ALPHA * (lbd as f64 - self.lbd_fast);
+		self.lbd_slow += LBD_SLOW_ALPHA * (lbd as f64 - self.lbd_slow);
+		self.log_proof(false, &lits);
+		let body = ClauseBody::new(lits.into_boxed_slice());
+		let ptr = body.ptr();
+		if ptr.0 != ptr.1 {
+			self.watch(ptr);
+		}
+		self.learned.push(Learned { body, ptr, lbd });
+		self.total_learned += 1;
+		if self.reduce_strategy == ReduceStrategy::Activity {
+			self.clause_activity.insert(ptr, 0.0);
+			self.bump_clause(ptr);
+		}
+		ptr
+	}
+
+	/// The number of distinct decision levels among the literals.
+	fn lbd(&self, lits: &[Lit]) -> usize {
+		let mut levels: Vec<_> = lits.iter().map(|lit| self.levels[lit.var()]).collect();
+		levels.sort_unstable();
+		levels.dedup();
+		levels.len()
+	}
+
+	/// Whether the clause is the reason of a current assignment.
+	fn is_reason(&self, ptr: ClausePtr) -> bool {
+		unsafe {
+			self.reasons[(*ptr.0).var()] == Some(ptr) || self.reasons[(*ptr.1).var()] == Some(ptr)
+		}
+	}
+
+	/// Deletes about half of the learned clauses, those with the highest LBD,
+	/// or the least active ones with [`ReduceStrategy::Activity`].
+	///
+	/// Clauses with LBD of at most 2 ("glue clauses"), or the binary ones by
+	/// the activity, and the reasons of the current assignments are kept.
+	fn reduce_learned(&mut self) {
+		let mut order: Vec<usize> = (0..self.learned.len()).collect();
+		let activity = |c: &Learned| self.clause_activity.get(&c.ptr).copied().unwrap_or(0.0);
+		match self.reduce_strategy {
+			ReduceStrategy::Lbd => order.sort_by_key(|&i| std::cmp::Reverse(self.learned[i].lbd)),
+			ReduceStrategy::Activity => order.sort_by(|&i, &j| {
+				activity(&self.learned[i]).total_cmp(&activity(&self.learned[j]))
+			}),
+		}
+		let mut delete = vec![false; self.learned.len()];
+		for &i in &order[..order.len() / 2] {
+			let c = &self.learned[i];
+			let kept = match self.reduce_strategy {
+				ReduceStrategy::Lbd => c.lbd <= 2,
+				ReduceStrategy::Activity => Self::lits(c.ptr).len() <= 2,
+			};
+			if !kept && !self.is_reason(c.ptr) {
+				delete[i] = true;
+			}
+		}
+		self.delete_learned(delete);
+	}
+
+	/// Deletes the learned clauses marked in `delete`, none of which is a
+	/// reason.
+	fn delete_learned(&mut self, delete: Vec<bool>) {
+		self.deleted_learned |= delete.contains(&true);
+		for (i, _) in delete.iter().enumerate().filter(|(_, &d)| d) {
+			let ptr = self.learned[i].ptr;
+			self.log_proof(true, Self::lits(ptr));
+			self.cores.remove(&ptr);
+			self.clause_activity.remove(&ptr);
+			if ptr.0 != ptr.1 {
+				self.unwatch(ptr);
+			}
+		}
+		let mut delete = delete.into_iter();
+		self.learned.retain(|_| !delete.next().unwrap());
+	}
+
+	/// Increases the activity of the variable.
+	///
+	/// When an activity exceeds `1e100`, all the activities and `var_inc` are
+	/// multiplied by `1e-100`, which keeps their order and avoids overflow.
+	fn bump(&mut self, var: usize) {
+		self.activity[var] += self.var_inc;
+		if self.activity[var] > 1e100 {
+			for a in &mut self.activity {
+				*a *= 1e-100;
+			}
+			self.var_inc *= 1e-100;
+		}
+		self.order.increased(var, &self.activity);
+	}
+
+	/// Increases the activity of the clause if it is a learned one with
+	/// [`ReduceStrategy::Activity`]. Above `1e20`, all the activities and
+	/// `clause_inc` are scaled down like in `bump()`.
+	fn bump_clause(&mut self, ptr: ClausePtr) {
+		if let Some(activity) = self.clause_activity.get_mut(&ptr) {
+			*activity += self.clause_inc;
+			if *activity > 1e20 {
+				for a in self.clause_activity.values_mut() {
+					*a *= 1e-20;
+				}
+				self.clause_inc *= 1e-20;
+			}
+		}
+	}
+
+	/// Sets the factor by which the activities decay on each conflict, `0.95`
+	/// by default as `var-decay` of MiniSat.
+	///
+	/// # Panics
+	///
+	/// Panics if `decay` is not in `(0, 1]`.
+	pub fn set_var_decay(&mut self, decay: f64) {
+		assert!(decay > 0.0 && decay <= 1.0, "var decay must be in (0, 1]");
+		self.var_decay = decay;
+	}
+
+	/// Sets the factor by which the activities of the learned clauses decay
+	/// on each conflict, `0.999` by default as `cla-decay` of MiniSat. They
+	/// are used only by [`ReduceStrategy::Activity`].
+	///
+	/// # Panics
+	///
+	/// Panics if `decay` is not in `(0, 1]`.
+	pub fn set_clause_decay(&mut self, decay: f64) {
+		assert!(
+			decay > 0.0 && decay <= 1.0,
+			"clause decay must be in (0, 1]"
+		);
+		self.clause_decay = decay;
+	}
+
+	/// Sets how the learned clauses are minimized, [`Minimization::Deep`] by
+	/// default.
+	pub fn set_ccmin_mode(&mut self, mode: Minimization) {
+		self.minimization = mode;
+	}
+
+	/// Sets which values are kept in `suggest` on unassigning,
+	/// [`PhaseSaving::Full`] by default.
+	pub fn set_phase_saving(&mut self, mode: PhaseSaving) {
+		self.phase_saving = mode;
+	}
+
+	/// Enables or disables rephasing, disabled by default. It overwrites
+	/// `suggest` on a restart after 8 restarts, then 16 more, 24 more and so
+	/// on, with the patterns in turn: all true, the best phase, all false, the
+	/// best phase, random values and the best phase. The best phase is the
+	/// assignment of the longest trail reached at a conflict, which is tracked
+	/// while it is enabled.
+	pub fn set_rephase(&mut self, enabled: bool) {
+		self.rephase = enabled;
+	}
+
+	/// Overwrites `suggest` with the next pattern of rephasing.
+	fn rephase(&mut self) {
+		match self.rephases % 6 {
+			0 => self.suggest.iter_mut().for_each(|s| *s = true),
+			2 => self.suggest.iter_mut().for_each(|s| *s = false),
+			4 => {
+				for var in 1..=self.n {
+					self.suggest[var] = self.random() & 1 == 1;
+				}
+			}
+			_ => {
+				self.suggest.copy_from_slice(&self.best_phase);
+				// The next best phase is searched for anew.
+				self.best_trail = 0;
+			}
+		}
+		self.rephases += 1;
+		self.rephase_countdown = REPHASE_INTERVAL * (self.rephases + 1);
+	}
+
+	/// Replaces the activities of the variables by the scores of the original
+	/// clauses given so far, so that the first decisions follow `mode` until
+	/// the conflicts bump the activities. The scores are scaled below one
+	/// bump, and the learned clauses are not counted. It takes time linear
+	/// in the size of the formula.
+	pub fn set_initial_order(&mut self, mode: InitialOrder) {
+		let mut scores = vec![0.0; self.n + 1];
+		if mode != InitialOrder::None {
+			for clause in self.clauses() {
+				let score = match mode {
+					InitialOrder::JeroslowWang => 0.5f64.powi(clause.len() as i32),
+					_ => 1.0,
+				};
+				for lit in clause {
+					scores[lit.var()] += score;
+				}
+			}
+		}
+		let max = scores.iter().copied().fold(0.0, f64::max);
+		if max > 0.0 {
+			for score in &mut scores {
+				*score *= self.var_inc / (2.0 * max);
+			}
+		}
+		self.activity = scores;
+		self.order = VarHeap::new(self.n);
+		for var in 1..=self.n {
+			self.order.insert(var, &self.activity);
+		}
+	}
+
+	/// Sets the number of conflicts between restarts, which is multiplied by
+	/// the Luby sequence `1, 1, 2, 1, 1, 2, 4, ...`. `0` disables restarts.
+	/// It is used only by [`RestartStrategy::Luby`].
+	pub fn set_restart_base(&mut self, base: usize) {
+		self.restart_base = base;
+	}
+
+	/// Enables or disables inprocessing: on each restart, the level 0
+	/// assignments are propagated, and the learned clauses satisfied by them
+	/// or subsumed by another clause are deleted. Disabled by default.
+	pub fn set_inprocessing(&mut self, enabled: bool) {
+		self.inprocessing = enabled;
+	}
+
+	/// Sets when the search restarts, [`RestartStrategy::Luby`] by default.
+	///
+	/// The LBD averages for [`RestartStrategy::Glucose`] are kept across the
+	/// solves and updated with any strategy, and the Luby sequence starts
+	/// over in each solve, so the strategy can be switched between solves.
+	pub fn set_restart_strategy(&mut self, strategy: RestartStrategy) {
+		self.restart_strategy = strategy;
+	}
+
+	/// Sets which learned clauses are deleted on a reduction,
+	/// [`ReduceStrategy::Lbd`] by default. The reasons of the current
+	/// assignments are never deleted.
+	///
+	/// The activities are kept only with [`ReduceStrategy::Activity`], so the
+	/// clauses learned before it is set start with no activity.
+	pub fn set_reduce_strategy(&mut self, strategy: ReduceStrategy) {
+		if strategy == ReduceStrategy::Activity && self.reduce_strategy != strategy {
+			for c in &self.learned {
+				self.clause_activity.insert(c.ptr, 0.0);
+			}
+		} else if strategy == ReduceStrategy::Lbd {
+			self.clause_activity.clear();
+			self.clause_inc = 1.0;
+		}
+		self.reduce_strategy = strategy;
+	}
+
+	/// Limits the number of conflicts in each solve. When the limit is
+	/// reached, the solve stops and returns [`SolveResult::Unknown`], keeping
+	/// the learned clauses so that the next solve can go further. `None`
+	/// removes the limit.
+	pub fn set_conflict_budget(&mut self, limit: Option<u64>) {
+		self.conflict_budget = limit;
+	}
+
+	/// Limits the time of each solve. The clock is checked once in every
+	/// 1024 conflicts, so the solve may run a little longer than `dur`. When
+	/// the time is over, the solve returns [`SolveResult::Unknown`] as with
+	/// the conflict budget. `None` removes the limit.
+	pub fn set_timeout(&mut self, dur: Option<Duration>) {
+		self.timeout = dur;
+	}
+
+	/// Sets the function polled during the search, with the timeout. When it
+	/// returns `true`, the solve returns [`SolveResult::Unknown`] as with the
+	/// conflict budget. It replaces the previous callback.
+	///
+	/// To interrupt a solve from another thread, let the callback read a flag
+	/// shared by an `Arc<AtomicBool>`.
+	pub fn set_terminate_callback<F: FnMut() -> bool + 'static>(&mut self, cb: F) {
+		self.terminate = Some(Box::new(cb));
+	}
+
+	/// Removes the callback set by [`Solver::set_terminate_callback`].
+	pub fn clear_terminate_callback(&mut self) {
+		self.terminate = None;
+	}
+
+	/// Sets the function called on each restart with the [`SolverStats`] at
+	/// the time, e.g. to print the progress. The trail then has only the
+	/// assignments at the level 0. It replaces the previous callback.
+	pub fn set_progress_callback<F: FnMut(&SolverStats) + 'static>(&mut self, cb: F) {
+		self.progress = Some(Box::new(cb));
+	}
+
+	/// Removes the callback set by [`Solver::set_progress_callback`].
+	pub fn clear_progress_callback(&mut self) {
+		self.progress = None;
+	}
+
+	/// Sets the function which picks each decision, after the assumptions.
+	/// It is given the solver with the partial assignment, which can be read
+	/// by [`Solver::value`], [`Solver::activity`] and
+	/// [`Solver::trail`], and returns the literal to decide, or `None` to
+	/// pick the variable with the highest activity as by default. It replaces
+	/// the previous function.
+	///
+	/// The solve panics if the function returns a literal which is zero, out
+	/// of `-n..=n` or assigned.
+	pub fn set_decision_fn<F: FnMut(&Solver) -> Option<LitInt> + 'static>(&mut self, f: F) {
+		self.decision = Some(Box::new(f));
+	}
+
+	/// Removes the function set by [`Solver::set_decision_fn`].
+	pub fn clear_decision_fn(&mut self) {
+		self.decision = None;
+	}
+
+	/// Whether the search should stop by the deadline or the terminate
+	/// callback.
+	fn interrupted(&mut self, deadline: Option<Instant>) -> bool {
+		deadline.is_some_and(|deadline| Instant::now() >= deadline)
+			|| self.terminate.as_mut().is_some_and(|cb| cb())
+	}
+
+	/// The number of conflicts in all the solves so far.
+	pub fn conflicts(&self) -> u64 {
+		self.conflicts
+	}
+
+	/// The number of decisions in all the solves so far, including the
+	/// assumptions.
+	pub fn decisions(&self) -> u64 {
+		self.decisions
+	}
+
+	/// The counters of the work done so far.
+	pub fn stats(&self) -> SolverStats {
+		SolverStats {
+			propagations: self.propagations,
+			traversals: self.traversals,
+			relocations: self.relocations,
+			conflicts: self.conflicts,
+			decisions: self.decisions,
+			learned: self.learned.len(),
+			trail: self.trail.len(),
+			restarts: self.total_restarts,
+			max_trail: self.max_trail,
+			total_learned: self.total_learned,
+		}
+	}
+
+	/// The number of restarts in all the solves so far.
+	pub fn restarts(&self) -> u64 {
+		self.total_restarts
+	}
+
+	/// The number of literals assigned in all the solves so far, including
+	/// decisions.
+	pub fn propagations(&self) -> u64 {
+		self.propagations
+	}
+
+	/// The largest number of literals assigned at once so far.
+	pub fn max_trail(&self) -> usize {
+		self.max_trail
+	}
+
+	/// The number of clauses learned so far, including the deleted ones,
+	/// while [`Solver::num_learned`] counts the ones currently kept.
+	pub fn total_learned(&self) -> u64 {
+		self.total_learned
+	}
+
+	/// Sets the counters of [`Solver::stats`] to `0`, except the current sizes
+	/// of the learned clauses and the trail. The counters are kept across
+	/// the solves until this is called.
+	pub fn reset_stats(&mut self) {
+		// The next reduction is scheduled by the number of conflicts.
+		self.next_reduce = self.next_reduce.saturating_sub(self.conflicts);
+		self.propagations = 0;
+		self.traversals = 0;
+		self.relocations = 0;
+		self.conflicts = 0;
+		self.decisions = 0;
+		self.total_restarts = 0;
+		self.max_trail = self.trail.len();
+		self.total_learned = 0;
+	}
+
+	/// Sets the probability of deciding a random unassigned variable instead
+	/// of the most active one. `0.0`, the default, never draws a random
+	/// number, so the search is the same as without this setting. This is
+	/// `rnd-freq` of MiniSat.
+	///
+	/// # Panics
+	///
+	/// Panics if `freq` is not in `[0, 1]`.
+	pub fn set_random_var_freq(&mut self, freq: f64) {
+		assert!(
+			(0.0..=1.0).contains(&freq),
+			"random var freq must be in [0, 1]"
+		);
+		self.random_var_freq = freq;
+	}
+
+	/// Sets the seed of the random decisions, so that a search with the same
+	/// seed and the same formula makes the same decisions.
+	pub fn set_seed(&mut self, seed: u64) {
+		// The xorshift generator is stuck at `0`.
+		self.rng = if seed == 0 { DEFAULT_SEED } else { seed };
+	}
+
+	/// Enables or disables chronological backtracking. When enabled, the
+	/// solver backtracks only to the level below the conflict if the learned
+	/// clause would jump back more than 100 levels, keeping the assignments
+	/// which are likely to be made again. Disabled by default.
+	///
+	/// The implied literals are then assigned at the highest level of their
+	/// reasons, so that the levels on the trail are no longer in order.
+	pub fn set_chronological(&mut self, enabled: bool) {
+		self.chrono_threshold = if enabled {
+			Some(CHRONO_THRESHOLD)
+		} else {
+			None
+		};
+	}
+
+	/// Sets the preferred values of the variables on decisions in `suggest`,
+	/// e.g. to start from the model of a similar formula. `phases[i]` is for
+	/// the variable `i + 1`, and `None` keeps its current value. As with the
+	/// other values in `suggest`, they are overwritten by phase saving.
+	///
+	/// Returns an error if `phases` does not have one element for each
+	/// variable, in which case nothing is changed.
+	pub fn set_polarities(&mut self, phases: &[Option<bool>]) -> Result<(), PolarityError> {
+		if phases.len() != self.n {
+			return Err(PolarityError {
+				expected: self.n,
+				found: phases.len(),
+			});
+		}
+		for (suggest, phase) in self.suggest[1..].iter_mut().zip(phases) {
+			if let Some(phase) = *phase {
+				*suggest = phase;
+			}
+		}
+		Ok(())
+	}
+
+	/// The next number of the xorshift64 generator.
+	fn random(&mut self) -> u64 {
+		self.rng ^= self.rng << 13;
+		self.rng ^= self.rng >> 7;
+		self.rng ^= self.rng << 17;
+		self.rng
+	}
+
+	/// Picks the unassigned variable with the highest activity as the next
+	/// decision, with the value in `suggest`. Returns `None` if all variables
+	/// are assigned.
+	///
+	/// The function set by `set_decision_fn()` is asked first. With the
+	/// probability `random_var_freq`, a random variable in `order` is tried next.
+	/// It is left in `order`, which may hold assigned variables.
+	fn pick_branch(&mut self) -> Option<Lit> {
+		let lit = |var: usize, value: bool| {
+			let var = var as LitInt;
+			Lit::new(if value { var } else { -var })
+		};
+		if let Some(mut f) = self.decision.take() {
+			let decision = f(self);
+			self.decision = Some(f);
+			if let Some(decision) = decision {
+				assert!(
+					decision != 0
+						&& decision.unsigned_abs() as usize <= self.n
+						&& self.assign[decision.unsigned_abs() as usize].is_none(),
+					"bad decision {}",
+					decision
+				);
+				return Lit::new(decision);
+			}
+		}
+		if self.random_var_freq > 0.0 && !self.order.is_empty() {
+			// 53 random bits in `[0, 1)`.
+			if (self.random() >> 11) as f64 / (1u64 << 53) as f64 <= self.random_var_freq {
+				let i = self.random() % self.order.len() as u64;
+				let var = self.order.get(i as usize);
+				if self.assign[var].is_none() {
+					return lit(var, self.suggest[var]);
+				}
+			}
+		}
+		while let Some(var) = self.order.pop(&self.activity) {
+			if self.assign[var].is_none() {
+				return lit(var, self.suggest[var]);
+			}
+		}
+		None
+	}
+
+	/// Solves the formula under the assumptions pushed by
+	/// [`Solver::push_assumption`]. If it is satisfiable, the satisfying
+	/// assignment is left in the solver.
+	pub fn solve(&mut self) -> SolveResult {
+		self.solve_with_assumptions(&[])
+	}
+
+	/// Solves the formula under the assumptions, which are the literals
+	/// assumed to be true only during this call, after the ones pushed by
+	/// [`Solver::push_assumption`].
+	///
+	/// If the formula is unsatisfiable under the assumptions,
+	/// [`Solver::failed_assumptions`] tells the assumptions responsible.
+	/// The learned clauses, the activities and the saved phases are kept for
+	/// the later calls so that they benefit from this search, and only the
+	/// assignments above the level 0 are undone. See
+	/// [`Solver::reset_heuristics`] for a cold start.
+	///
+	/// # Panics
+	///
+	/// Panics if some assumption is zero or out of `-n..=n`.
+	pub fn solve_with_assumptions(&mut self, assumptions: &[LitInt]) -> SolveResult {
+		let n = self.n;
+		self.assumptions.clone_from(&self.assumption_stack);
+		self.assumptions.extend(assumptions.iter().map(|&lit| {
+			assert!(
+				lit != 0 && lit.unsigned_abs() as usize <= n,
+				"bad assumption {}",
+				lit
+			);
+			Lit::new(lit).unwrap()
+		}));
+		self.failed.clear();
+		self.backjump(0);
+		self.restarts = 0;
+		self.restart_conflicts = 0;
+		let result = self.search();
+		self.status = match result {
+			SolveResult::Sat => Some(true),
+			SolveResult::Unsat => Some(false),
+			SolveResult::Unknown => None,
+		};
+		if !result.is_sat() {
+			self.backjump(0);
+		}
+		debug_assert_eq!(self.verify_watch_invariant(), Ok(()));
+		debug_assert!(!result.is_sat() || self.check_model());
+		result
+	}
+
+	/// Solves the formula for at most `conflicts` conflicts, returning
+	/// [`SolveResult::Unknown`] if it is not decided yet, so that the search
+	/// can run in slices.
+	///
+	/// Unlike a solve stopped by the conflict budget, the assignment is kept
+	/// with the learned clauses, the activities and the position in the
+	/// restart sequence, and the next call resumes the search where it
+	/// stopped. A slice stopped on a conflict undoes the level of the
+	/// conflict. After a decided solve, a solve under assumptions or a change
+	/// of the clauses, it starts over at the level 0. The limit replaces the
+	/// conflict budget, and the timeout and the terminate callback still
+	/// apply.
+	///
+	/// The search is under the assumptions pushed by
+	/// [`Solver::push_assumption`], and starts over when they are pushed.
+	pub fn solve_limited(&mut self, conflicts: u64) -> SolveResult {
+		if self.status.is_some() || self.assumptions != self.assumption_stack {
+			self.assumptions.clone_from(&self.assumption_stack);
+			self.backjump(0);
+			self.restarts = 0;
+			self.restart_conflicts = 0;
+		}
+		self.failed.clear();
+		let budget = self.conflict_budget.replace(conflicts);
+		let result = self.search();
+		self.conflict_budget = budget;
+		self.status = match result {
+			SolveResult::Sat => Some(true),
+			SolveResult::Unsat => Some(false),
+			SolveResult::Unknown => None,
+		};
+		if result.is_unsat() {
+			self.backjump(0);
+		}
+		debug_assert_eq!(self.verify_watch_invariant(), Ok(()));
+		debug_assert!(!result.is_sat() || self.check_model());
+		result
+	}
+
+	/// Pushes the assumption `lit`, which the later solves assume to be true
+	/// until it is popped by [`Solver::pop_assumption`].
+	///
+	/// # Panics
+	///
+	/// Panics if `lit` is zero or out of `-n..=n`.
+	pub fn push_assumption(&mut self, lit: LitInt) {
+		assert!(
+			lit != 0 && lit.unsigned_abs() as usize <= self.n,
+			"bad assumption {}",
+			lit
+		);
+		self.assumption_stack.push(Lit::new(lit).unwrap());
+	}
+
+	/// Pops the assumption pushed last, undoing it and what is propagated from
+	/// it. The learned clauses are kept, and the result of the last solve is
+	/// forgotten.
+	///
+	/// # Panics
+	///
+	/// Panics if no assumption is pushed.
+	pub fn pop_assumption(&mut self) {
+		assert!(
+			self.assumption_stack.pop().is_some(),
+			"no assumption to pop"
+		);
+		// The levels from 1 are those of the assumptions still pushed.
+		let kept = self
+			.assumptions
+			.iter()
+			.zip(&self.assumption_stack)
+			.take_while(|(p, q)| p == q)
+			.count();
+		self.assumptions.truncate(kept);
+		if self.level > kept {
+			self.backjump(kept);
+		}
+		self.status = None;
+		self.failed.clear();
+	}
+
+	/// Enumerates all the models, passing each to `cb` as the values of the
+	/// variables `1..=n` like [`Solver::model`]. The enumeration stops when
+	/// `cb` returns `false`.
+	///
+	/// Each model found is excluded by adding the clause blocking it, which
+	/// stays in the formula afterwards. The number of models can be `2^n` in
+	/// the worst case, so `cb` should stop early if that is possible.
+	///
+	/// Returns [`SolveResult::Unsat`] if all the models are enumerated,
+	/// [`SolveResult::Sat`] if `cb` stopped it, and [`SolveResult::Unknown`]
+	/// if the conflict budget or the timeout stopped it.
+	pub fn solve_all<F: FnMut(&[bool]) -> bool>(&mut self, mut cb: F) -> SolveResult {
+		loop {
+			match self.solve() {
+				SolveResult::Sat => {}
+				result => return result,
+			}
+			if !cb(&self.model().unwrap()) {
+				return SolveResult::Sat;
+			}
+			self.block_current_model().unwrap();
+		}
+	}
+
+	/// Adds the clause excluding the model of the last solve, the negation of
+	/// the values of all the variables in [`Solver::model`], so that models
+	/// can be enumerated by a loop of [`Solver::solve`] and this, like
+	/// [`Solver::solve_all`]. The clause stays in the formula.
+	///
+	/// It requires the model of a satisfiable solve, and returns
+	/// [`ClauseError::NoModel`] otherwise, e.g. after a clause is added.
+	pub fn block_current_model(&mut self) -> Result<(), ClauseError> {
+		let model = self.model().ok_or(ClauseError::NoModel)?;
+		let blocking: Vec<_> = (1..=self.n as LitInt)
+			.map(|var| if model[var as usize - 1] { -var } else { var })
+			.collect();
+		self.add_clause(&blocking)
+	}
+
+	/// Solves the formula preferring a model far from `prev`, by setting the
+	/// preferred value of each variable on decisions to the opposite of the
+	/// one in `prev` before solving. `prev[i]` is for the variable `i + 1`,
+	/// like [`Solver::model`]. Returns the model, or `None` if the solve does
+	/// not find one.
+	///
+	/// It only biases the decisions, so the model may still be close to or
+	/// the same as `prev`. Unlike [`Solver::block_current_model`], no clause
+	/// is added.
+	///
+	/// # Panics
+	///
+	/// Panics if `prev` does not have one value for each variable.
+	pub fn solve_diverse(&mut self, prev: &[bool]) -> Option<Vec<bool>> {
+		assert_eq!(prev.len(), self.n, "bad number of values");
+		// Unassign first, which would save the phases over them.
+		self.backjump(0);
+		for (suggest, &value) in self.suggest[1..].iter_mut().zip(prev) {
+			*suggest = !value;
+		}
+		if self.solve().is_sat() {
+			self.model()
+		} else {
+			None
+		}
+	}
+
+	/// Counts the models projected onto `vars`, that is, the assignments to
+	/// `vars` which extend to a model. Models differing only outside `vars`
+	/// are counted once.
+	///
+	/// Like [`Solver::solve_all`], each projected model is excluded by a
+	/// blocking clause over `vars`, which stays in the formula. If the
+	/// conflict budget or the timeout stops the enumeration, the number of
+	/// models found so far is returned.
+	///
+	/// # Panics
+	///
+	/// Panics if a variable is out of `1..=n`.
+	pub fn count_models_projected(&mut self, vars: &[LitInt]) -> u64 {
+		for &var in vars {
+			assert!(var > 0 && var as usize <= self.n, "bad variable {}", var);
+		}
+		let mut vars = vars.to_vec();
+		vars.sort_unstable();
+		vars.dedup();
+		let mut count = 0;
+		while self.solve().is_sat() {
+			count += 1;
+			let blocking: Vec<_> = vars
+				.iter()
+				.map(|&var| {
+					if self.model_value(var).unwrap() {
+						-var
+					} else {
+						var
+					}
+				})
+				.collect();
+			self.add_clause(&blocking).unwrap();
+		}
+		count
+	}
+
+	/// Solves the formula like [`Solver::solve`], writing the DRAT proof of
+	/// unsatisfiability to `out`.
+	///
+	/// The proof consists of the clauses learned and deleted in this call, and
+	/// ends with the empty clause if the formula is unsatisfiable. Lines are
+	/// buffered during the search and written when it finishes.
+	pub fn solve_with_proof<W: Write>(&mut self, mut out: W) -> io::Result<SolveResult> {
+		self.proof = Some(Vec::new());
+		let result = self.solve();
+		if self.unsat {
+			self.log_proof(false, &[]);
+		}
+		out.write_all(&self.proof.take().unwrap())?;
+		out.flush()?;
+		Ok(result)
+	}
+
+	/// Records the addition (or deletion if `deleted`) of the clause to the
+	/// proof, if it is requested.
+	fn log_proof(&mut self, deleted: bool, lits: &[Lit]) {
+		if let Some(proof) = &mut self.proof {
+			if deleted {
+				proof.extend_from_slice(b"d ");
+			}
+			for lit in lits {
+				write!(proof, "{} ", lit).unwrap();
+			}
+			proof.extend_from_slice(b"0\n");
+		}
+	}
+
+	/// The assumptions which made the last [`Solver::solve_with_assumptions`]
+	/// unsatisfiable. Empty if the formula is unsatisfiable without them.
+	pub fn failed_assumptions(&self) -> Vec<LitInt> {
+		self.failed.clone()
+	}
+
+	/// The literals which are true in every model under the assumptions of
+	/// the last satisfiable solve, as found by unit propagation from the
+	/// assumptions and the clauses, including the learned ones. They are read
+	/// off the trail at the level 0 and the levels of the assumptions, in the
+	/// order of assignment, and the assumptions themselves are left out.
+	///
+	/// Empty unless the last solve is satisfiable. Literals which only
+	/// follow by a search are not found.
+	pub fn implied_literals(&self) -> Vec<LitInt> {
+		if self.status != Some(true) {
+			return Vec::new();
+		}
+		self.trail
+			.iter()
+			.filter(|lit| {
+				self.levels[lit.var()] <= self.assumptions.len() && !self.assumptions.contains(lit)
+			})
+			.map(|lit| lit.get())
+			.collect()
+	}
+
+	/// Enables or disables recording the original clauses from which each
+	/// learned clause is derived, for [`Solver::unsat_core`]. Disabled by
+	/// default, since it takes time and memory on each conflict.
+	///
+	/// The clauses learned before it is enabled are taken as derived from all
+	/// the original clauses.
+	pub fn set_core_tracking(&mut self, enabled: bool) {
+		if enabled && !self.core_tracking {
+			for c in &self.learned {
+				self.cores.insert(c.ptr, None);
+			}
+			for &lit in &self.units {
+				let unit = self
+					.clauses
+					.iter()
+					.find(|ptr| ptr.0 == ptr.1 && unsafe { *ptr.0 } == lit);
+				self.unit_cores.insert(lit, unit.map(|&ptr| vec![ptr]));
+			}
+		} else if !enabled {
+			self.cores.clear();
+			self.unit_cores.clear();
+		}
+		self.core_tracking = enabled;
+	}
+
+	/// The original clauses which imply the unsatisfiability found by the last
+	/// solve, together with [`Solver::failed_assumptions`], in the order of
+	/// [`Solver::clauses`]. Empty unless the last solve is unsatisfiable.
+	///
+	/// They are traced from the final conflict through the reasons and the
+	/// clauses from which the learned ones are derived, with
+	/// [`Solver::set_core_tracking`] enabled. Otherwise, all the original
+	/// clauses are returned. The core is not minimal in general. The clauses
+	/// removed or rewritten by the preprocessing, e.g.
+	/// [`Solver::eliminate_vars`], are not traced, so the core may miss what
+	/// they imply.
+	pub fn unsat_core(&self) -> Vec<ClauseRef> {
+		if self.status != Some(false) {
+			return Vec::new();
+		}
+		let core: Option<HashSet<ClausePtr>> = self
+			.final_core
+			.as_ref()
+			.map(|core| core.iter().copied().collect());
+		(0..self.clauses.len())
+			.filter(|&i| {
+				core.as_ref()
+					.is_none_or(|core| core.contains(&self.clauses[i]))
+			})
+			.map(|i| self.clause_ref(i))
+			.collect()
+	}
+
+	/// The original clauses from which the clauses `clauses` and the values
+	/// of the false literals `lits` are derived, following the reasons except
+	/// those of the decisions and of the literals in `stop`.
+	///
+	/// Returns `None` if a clause learned before the tracking is used.
+	fn trace(&self, clauses: &[ClausePtr], lits: &[Lit], stop: &[Lit]) -> Option<Vec<ClausePtr>> {
+		let mut visited: HashSet<usize> = stop.iter().map(Lit::var).collect();
+		let mut core = Vec::new();
+		let mut stack = clauses.to_vec();
+		let mut pending = lits.to_vec();
+		loop {
+			for lit in pending.drain(..) {
+				let var = lit.var();
+				if !visited.insert(var) {
+					continue;
+				}
+				match self.reasons[var] {
+					Some(reason) => stack.push(reason),
+					// A unit clause.
+					None if self.levels[var] == 0 => {
+						core.extend(self.unit_cores.get(&-lit)?.as_ref()?);
+					}
+					None => {}
+				}
+			}
+			let ptr = match stack.pop() {
+				Some(ptr) => ptr,
+				None => break,
+			};
+			match self.cores.get(&ptr) {
+				Some(learned) => core.extend(learned.as_ref()?),
+				None => core.push(ptr),
+			}
+			// The implied literal is already visited.
+			pending.extend_from_slice(Self::lits(ptr));
+		}
+		core.sort_unstable();
+		core.dedup();
+		Some(core)
+	}
+
+	/// Records the core of the unsatisfiability found by the false clauses
+	/// and literals, if the tracking is enabled.
+	fn record_core(&mut self, clauses: &[ClausePtr], lits: &[Lit]) {
+		self.final_core = if self.core_tracking {
+			self.trace(clauses, lits, &[])
+		} else {
+			None
+		};
+	}
+
+	/// Collects the assumptions which imply `-p` into `failed`, where `p` is
+	/// an assumption found false.
+	fn analyze_final(&mut self, p: Lit) {
+		self.failed = vec![p.as_int()];
+		self.record_core(&[], &[p]);
+		if self.levels[p.var()] == 0 {
+			return;
+		}
+		self.seen[p.var()] = true;
+		for i in (0..self.trail.len()).rev() {
+			let lit = self.trail[i];
+			let var = lit.var();
+			// The trail may have the level 0 after the higher levels.
+			if self.levels[var] == 0 || !self.seen[var] {
+				continue;
+			}
+			match self.reasons[var] {
+				// Only assumptions are decided at these levels.
+				None => self.failed.push(lit.as_int()),
+				Some(reason) => {
+					for &lit in Self::lits(reason) {
+						if self.levels[lit.var()] > 0 {
+							self.seen[lit.var()] = true;
+						}
+					}
+				}
+			}
+			self.seen[var] = false;
+		}
+	}
+
+	/// Assigns the unit clauses at the level 0 and propagates them to
+	/// fixpoint. The assignments stay until [`Solver::reset_assignment`].
+	///
+	/// Returns `false` if the formula is found unsatisfiable, e.g. it has
+	/// both `x` and `-x` as unit clauses.
+	fn simplify(&mut self) -> bool {
+		debug_assert_eq!(self.level, 0);
+		if self.unsat {
+			return false;
+		}
+		for i in 0..self.units.len() {
+			let lit = self.units[i];
+			match unsafe { self.eval_unchecked(lit) } {
+				Some(true) => {}
+				Some(false) => {
+					self.unsat = true;
+					self.record_core(&[], &[lit]);
+					// The unit clause `lit` is also used.
+					let unit = self.unit_cores.get(&lit).cloned().flatten();
+					self.final_core = self.final_core.take().zip(unit).map(|(mut core, unit)| {
+						core.extend(unit);
+						core
+					});
+					return false;
+				}
+				None => self.set(lit, None),
+			}
+		}
+		if let Some(conflict) = self.propagate() {
+			if cfg!(debug_assertions) {
+				self.check_unsat_trail(conflict);
+			}
+			self.unsat = true;
+			self.record_core(&[conflict], &[]);
+		}
+		!self.unsat
+	}
+
+	/// Simplifies the formula at the level 0 on a restart: propagates the
+	/// literals assigned so far, and deletes the learned clauses which are
+	/// satisfied at the level 0 or subsumed by another clause.
+	///
+	/// The reasons of the level 0 assignments are kept, and the other learned
+	/// clauses are not reasons after the restart. Returns `false` if the
+	/// formula is found unsatisfiable.
+	fn inprocess(&mut self) -> bool {
+		if !self.simplify() {
+			return false;
+		}
+		let mut delete: Vec<bool> = self
+			.learned
+			.iter()
+			.map(|c| {
+				!self.is_reason(c.ptr)
+					&& Self::lits(c.ptr)
+						.iter()
+						.any(|&lit| unsafe { self.eval_unchecked(lit) } == Some(true))
+			})
+			.collect();
+		let mut occurs = vec![Vec::new(); 2 * (self.n + 1)];
+		for (i, c) in self.learned.iter().enumerate() {
+			if !delete[i] {
+				for lit in Self::lits(c.ptr) {
+					occurs[lit.get_loc()].push(i);
+				}
+			}
+		}
+		let mut marked = vec![false; 2 * (self.n + 1)];
+		let subsumers = self.clauses.iter().map(|&ptr| (ptr, None)).chain(
+			self.learned
+				.iter()
+				.enumerate()
+				.map(|(i, c)| (c.ptr, Some(i))),
+		);
+		for (ptr, j) in subsumers {
+			if j.is_some_and(|j| delete[j]) {
+				continue;
+			}
+			let lits = Self::lits(ptr);
+			let rarest = lits
+				.iter()
+				.min_by_key(|lit| occurs[lit.get_loc()].len())
+				.unwrap();
+			for lit in lits {
+				marked[lit.get_loc()] = true;
+			}
+			for &i in &occurs[rarest.get_loc()] {
+				let other = Self::lits(self.learned[i].ptr);
+				// Of the same learned clauses, the first one is kept.
+				let before = j.is_none_or(|j| j < i || lits.len() < other.len());
+				if delete[i] || !before || other.len() < lits.len() {
+					continue;
+				}
+				let common = other.iter().filter(|lit| marked[lit.get_loc()]).count();
+				if common == lits.len() && !self.is_reason(self.learned[i].ptr) {
+					delete[i] = true;
+				}
+			}
+			for lit in lits {
+				marked[lit.get_loc()] = false;
+			}
+		}
+		self.delete_learned(delete);
+		true
+	}
+
+	/// Asserts that the conflict at the level 0 proves the unsatisfiability,
+	/// which is checked in debug builds.
+	///
+	/// The conflict must be falsified, and each literal leading to it must be
+	/// a unit clause or implied by its reason through the literals assigned
+	/// before. A learned clause among the reasons must be derived by unit
+	/// propagation from the other clauses, which is checked only while no
+	/// learned clause or original clause is deleted, by the naive solver. It
+	/// is not a full proof check.
+	fn check_unsat_trail(&self, conflict: ClausePtr) {
+		let mut position = vec![usize::MAX; self.n + 1];
+		for (i, lit) in self.trail.iter().enumerate() {
+			position[lit.var()] = i;
+		}
+		let false_before = |lit: Lit, i: usize| {
+			let value = unsafe { self.eval_unchecked(lit) };
+			value == Some(false) && self.levels[lit.var()] == 0 && position[lit.var()] < i
+		};
+		assert!(
+			Self::lits(conflict)
+				.iter()
+				.all(|&lit| false_before(lit, usize::MAX)),
+			"the conflict is not falsified at the level 0"
+		);
+		let mut seen = vec![false; self.n + 1];
+		let mut stack: Vec<_> = Self::lits(conflict).iter().map(Lit::var).collect();
+		let mut learned = Vec::new();
+		while let Some(var) = stack.pop() {
+			if std::mem::replace(&mut seen[var], true) {
+				continue;
+			}
+			let lit = self.trail[position[var]];
+			match self.reasons[var] {
+				None => assert!(
+					self.units.contains(&lit),
+					"{} is decided at the level 0",
+					lit
+				),
+				Some(reason) => {
+					let lits = Self::lits(reason);
+					assert!(
+						lits.contains(&lit)
+							&& lits
+								.iter()
+								.all(|&l| l == lit || false_before(l, position[var])),
+						"{} is not implied by its reason",
+						lit
+					);
+					if !self.clauses.contains(&reason) {
+						learned.push(reason);
+					}
+					stack.extend(lits.iter().map(Lit::var));
+				}
+			}
+		}
+		if self.deleted_learned || !self.eliminated.is_empty() {
+			return;
+		}
+		let ints = |lits: &[Lit]| -> Vec<LitInt> { lits.iter().map(|l| l.get()).collect() };
+		for ptr in learned {
+			let others: Vec<_> = self
+				.clauses
+				.iter()
+				.chain(self.learned.iter().map(|c| &c.ptr))
+				.filter(|&&p| p != ptr)
+				.map(|&p| ints(Self::lits(p)))
+				.chain(self.units.iter().map(|&lit| vec![lit.get()]))
+				.collect();
+			assert!(
+				naive::implied_by_propagation(others, &ints(Self::lits(ptr))),
+				"the learned clause {:?} is not implied",
+				ints(Self::lits(ptr))
+			);
+		}
+	}
+
+	/// Whether the conflicts since `start` have used up the conflict budget.
+	fn out_of_budget(&self, start: u64) -> bool {
+		matches!(self.conflict_budget, Some(limit) if self.conflicts - start >= limit)
+	}
+
+	fn search(&mut self) -> SolveResult {
+		// A resumed search keeps its levels, and the level 0 is simplified.
+		if self.unsat || (self.level == 0 && !self.simplify()) {
+			return SolveResult::Unsat;
+		}
+		let start = self.conflicts;
+		let deadline = self.timeout.map(|dur| Instant::now() + dur);
+		let maintain = deadline.is_some() || self.terminate.is_some();
+		let mut next_check = start + CHECK_INTERVAL;
+		loop {
+			if self.out_of_budget(start) {
+				return SolveResult::Unknown;
+			}
+			if maintain && self.conflicts >= next_check {
+				next_check = self.conflicts + CHECK_INTERVAL;
+				if self.interrupted(deadline) {
+					return SolveResult::Unknown;
+				}
+			}
+			if self.conflicts >= self.next_reduce {
+				self.reduce_learned();
+				self.next_reduce = self.conflicts + REDUCE_FIRST + REDUCE_INC * self.reductions;
+				self.reductions += 1;
+			}
+			let restart = match self.restart_strategy {
+				RestartStrategy::Luby => {
+					self.restart_base > 0
+						&& self.restart_conflicts >= luby(self.restarts) * self.restart_base
+				}
+				RestartStrategy::Glucose => {
+					self.restart_conflicts >= GLUCOSE_MIN_CONFLICTS
+						&& self.lbd_fast > GLUCOSE_MARGIN * self.lbd_slow
+				}
+				RestartStrategy::None => false,
+			};
+			if restart {
+				// Learned clauses and saved phases are kept.
+				self.backjump(0);
+				self.restarts += 1;
+				self.total_restarts += 1;
+				self.restart_conflicts = 0;
+				if self.rephase {
+					self.rephase_countdown -= 1;
+					if self.rephase_countdown == 0 {
+						self.rephase();
+					}
+				}
+				if self.inprocessing && !self.inprocess() {
+					return SolveResult::Unsat;
+				}
+				let stats = self.stats();
+				if let Some(cb) = &mut self.progress {
+					cb(&stats);
+				}
+			}
+			let lit = loop {
+				// Decide the assumptions first, one for each level.
+				if let Some(&p) = self.assumptions.get(self.level) {
+					match unsafe { self.eval_unchecked(p) } {
+						Some(true) => {
+							// Keep the level without assignments.
+							self.level += 1;
+							continue;
+						}
+						Some(false) => {
+							self.analyze_final(p);
+							return SolveResult::Unsat;
+						}
+						None => break p,
+					}
+				}
+				match self.pick_branch() {
+					Some(lit) => break lit,
+					None => return SolveResult::Sat,
+				}
+			};
+			self.level += 1;
+			self.decisions += 1;
+			let mut conflict = self.assign_and_propagate(lit, None);
+			while let Some(ptr) = conflict {
+				if self.rephase && self.trail.len() > self.best_trail {
+					self.best_trail = self.trail.len();
+					for &lit in &self.trail {
+						self.best_phase[lit.var()] = lit.as_int() > 0;
+					}
+				}
+				// The conflict may be below the current level with chronological
+				// backtracking.
+				let level = if self.chrono_threshold.is_some() {
+					unsafe {
+						self.watch_highest(ptr, ptr.0);
+						self.watch_highest(ptr, ptr.1);
+					}
+					Self::lits(ptr)
+						.iter()
+						.map(|lit| self.levels[lit.var()])
+						.max()
+						.unwrap()
+				} else {
+					self.level
+				};
+				if level == 0 {
+					if cfg!(debug_assertions) {
+						self.check_unsat_trail(ptr);
+					}
+					// Adding clauses never makes it satisfiable again.
+					self.unsat = true;
+					self.record_core(&[ptr], &[]);
+					return SolveResult::Unsat;
+				}
+				if self.out_of_budget(start) {
+					// Undo the level of the conflict, where the next solve may
+					// find it again.
+					self.backjump(level - 1);
+					return SolveResult::Unknown;
+				}
+				if level < self.level {
+					self.backjump(level);
+				}
+				self.restart_conflicts += 1;
+				self.conflicts += 1;
+				let (learned, level) = self.analyze(ptr);
+				self.var_inc /= self.var_decay;
+				if self.reduce_strategy == ReduceStrategy::Activity {
+					self.clause_inc /= self.clause_decay;
+				}
+				let core = if self.core_tracking {
+					Some(self.trace(&[ptr], &[], &learned))
+				} else {
+					None
+				};
+				let lit = learned[0];
+				let ptr = self.learn(learned);
+				if let Some(core) = core {
+					self.cores.insert(ptr, core);
+				}
+				match self.chrono_threshold {
+					// `lit` is assigned at `level` after the higher levels.
+					Some(threshold) if self.level - level > threshold => {
+						self.backjump(self.level - 1)
+					}
+					_ => self.backjump(level),
+				}
+				// The learned clause is unit at `level`.
+				conflict = self.assign_and_propagate(lit, Some(ptr));
+			}
+		}
+	}
+
+	/// Whether the formula is known to be unsatisfiable regardless of the
+	/// assumptions, e.g. by the empty clause or a conflict at the level 0.
+	/// Once set, it stays so, since adding clauses and variables never makes
+	/// the formula satisfiable again, and each solve returns
+	/// [`SolveResult::Unsat`] right away.
+	///
+	/// It is found by propagation, so it may be unset before a solve even if
+	/// the unit clauses contradict each other.
+	pub fn is_unsat(&self) -> bool {
+		self.unsat
+	}
+
+	/// Returns the satisfying assignment found by the last [`Solver::solve`],
+	/// or `None` if the formula has not been proven satisfiable.
+	///
+	/// The index `i` of the vector holds the value of the variable `i + 1`.
+	/// Variables left unassigned are reported as `false`. The clauses removed
+	/// by [`Solver::eliminate_blocked_clauses`] are satisfied by changing the
+	/// values of their blocking literals.
+	pub fn model(&self) -> Option<Vec<bool>> {
+		if self.status != Some(true) {
+			return None;
+		}
+		let mut model: Vec<bool> = self
+			.assign
+			.iter()
+			.skip(1)
+			.map(|v| v.unwrap_or(false))
+			.collect();
+		for (lit, clause) in self.eliminated.iter().rev() {
+			if !clause.iter().any(|l| model[l.var() - 1] == (l.get() > 0)) {
+				model[lit.var() - 1] = lit.get() > 0;
+			}
+		}
+		Some(model)
+	}
+
+	/// The value of the literal in the model, or `None` if the formula has not
+	/// been proven satisfiable. Consistent with [`Solver::model`].
+	///
+	/// # Panics
+	///
+	/// Panics if the literal is zero or out of `-n..=n`.
+	pub fn model_value(&self, lit: LitInt) -> Option<bool> {
+		assert!(
+			lit != 0 && lit.unsigned_abs() as usize <= self.n,
+			"bad literal {}",
+			lit
+		);
+		if self.status != Some(true) {
+			return None;
+		}
+		let value = if self.eliminated.is_empty() {
+			self.assign[lit.unsigned_abs() as usize].unwrap_or(false)
+		} else {
+			self.model().unwrap()[lit.unsigned_abs() as usize - 1]
+		};
+		Some(value == (lit > 0))
+	}
+
+	/// Whether [`Solver::model`] satisfies every original clause of
+	/// [`Solver::to_clauses`], which holds after a satisfiable solve, also
+	/// with the clauses removed or rewritten by the preprocessing. The
+	/// learned clauses are not used. Returns `false` if there is no model.
+	pub fn check_model(&self) -> bool {
+		let model = match self.model() {
+			Some(model) => model,
+			None => return false,
+		};
+		!self.empty
+			&& self.original_clauses().all(|clause| {
+				clause
+					.iter()
+					.any(|lit| model[lit.var() - 1] == (lit.get() > 0))
+			})
+	}
+
+	/// The current value of the variable, or `None` if it is unassigned.
+	///
+	/// Unlike [`Solver::model_value`], this is the partial assignment left by the
+	/// last search, e.g. the level 0 assignment after an unsatisfiable solve
+	/// under assumptions.
+	///
+	/// # Panics
+	///
+	/// Panics if the variable is out of `1..=n`.
+	pub fn value(&self, var: usize) -> Option<bool> {
+		assert!(var >= 1 && var <= self.n, "bad variable {}", var);
+		self.assign[var]
+	}
+
+	/// The decision level at which the variable is assigned, or `None` if it
+	/// is unassigned.
+	///
+	/// # Panics
+	///
+	/// Panics if the variable is out of `1..=n`.
+	pub fn level_of(&self, var: usize) -> Option<usize> {
+		self.value(var).map(|_| self.levels[var])
+	}
+
+	/// The VSIDS activity of the variable, which is bumped in each conflict
+	/// analysis and decays over time. Only the order among the variables is
+	/// meaningful, since all the activities are scaled down when one grows
+	/// too large.
+	///
+	/// # Panics
+	///
+	/// Panics if the variable is out of `1..=n`.
+	pub fn activity(&self, var: usize) -> f64 {
+		assert!(var >= 1 && var <= self.n, "bad variable {}", var);
+		self.activity[var]
+	}
+
+	/// The assigned literals in the assignment order, each with its decision
+	/// level.
+	///
+	/// The levels are not monotone with chronological backtracking, since an
+	/// implied literal may be assigned at a level lower than the current one.
+	pub fn trail(&self) -> Vec<(LitInt, usize)> {
+		self.trail
+			.iter()
+			.map(|lit| (lit.get(), self.levels[lit.var()]))
+			.collect()
+	}
+
+	/// Describes the implication graph of the conflict in Graphviz DOT, if a
+	/// clause is falsified by the current assignment, e.g. after the formula
+	/// is found unsatisfiable at the level 0.
+	///
+	/// A node is a true literal labeled with its level, and the nodes leading
+	/// to the conflict are shown in the assignment order. Decisions are boxes,
+	/// and an implied literal has an edge from each literal of its reason.
+	/// The conflict is the red node.
+	pub fn conflict_graph_dot(&self) -> Option<String> {
+		use std::fmt::Write as _;
+		let false_lit = |lit: &Lit| unsafe { self.eval_unchecked(*lit) } == Some(false);
+		let conflict = *self
+			.clauses
+			.iter()
+			.chain(self.learned.iter().map(|c| &c.ptr))
+			.find(|&&ptr| Self::lits(ptr).iter().all(false_lit))?;
+		let mut shown = vec![false; self.n + 1];
+		let mut stack: Vec<_> = Self::lits(conflict).iter().map(|lit| lit.var()).collect();
+		while let Some(var) = stack.pop() {
+			if !std::mem::replace(&mut shown[var], true) {
+				if let Some(reason) = self.reasons[var] {
+					stack.extend(Self::lits(reason).iter().map(|lit| lit.var()));
+				}
+			}
+		}
+		let mut dot = String::from("digraph conflict {\n");
+		for &lit in &self.trail {
+			let var = lit.var();
+			if !shown[var] {
+				continue;
+			}
+			let shape = if self.reasons[var].is_some() {
+				"ellipse"
+			} else {
+				"box"
+			};
+			writeln!(
+				dot,
+				"  \"{}\" [label=\"{} @ {}\", shape={}];",
+				lit, lit, self.levels[var], shape
+			)
+			.unwrap();
+			if let Some(reason) = self.reasons[var] {
+				for &l in Self::lits(reason) {
+					if l != lit {
+						writeln!(dot, "  \"{}\" -> \"{}\";", -l, lit).unwrap();
+					}
+				}
+			}
+		}
+		dot.push_str("  conflict [color=red, style=filled];\n");
+		for &l in Self::lits(conflict) {
+			writeln!(dot, "  \"{}\" -> conflict;", -l).unwrap();
+		}
+		dot.push_str("}\n");
+		Some(dot)
+	}
+}
+
+impl fmt::Debug for Solver {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		// The assigned literals, in the same form as the DIMACS model line.
+		let assign: Vec<_> = (1..=self.n)
+			.filter_map(|var| {
+				self.assign[var].map(|b| if b { var as LitInt } else { -(var as LitInt) })
+			})
+			.collect();
+		f.debug_struct("Solver")
+			.field("n", &self.n)
+			.field("clauses", &self.num_clauses())
+			.field("level", &self.level)
+			.field("assign", &assign)
+			.finish()
+	}
+}
+
+/// Copies the clauses into new bodies, and moves each pointer to a clause
+/// onto the copy. The terminate, progress and decision callbacks are not
+/// cloned.
+impl Clone for Solver {
+	fn clone(&self) -> Self {
+		let copy = |body: &ClauseBody| ClauseBody::new(unsafe { &*body.0 }.into());
+		let clause_body: Vec<_> = self.clause_body.iter().map(copy).collect();
+		let learned_body: Vec<_> = self.learned.iter().map(|c| copy(&c.body)).collect();
+		// The old and the new base of each body, ordered by the old one.
+		let mut bases: Vec<(*mut Lit, *mut Lit)> = self
+			.clause_body
+			.iter()
+			.zip(&clause_body)
+			.chain(self.learned.iter().map(|c| &c.body).zip(&learned_body))
+			.map(|(old, new)| (old.base(), new.base()))
+			.collect();
+		bases.sort_unstable();
+		let rebase = |ptr: ClausePtr| -> ClausePtr {
+			let (old, new) = bases[bases.partition_point(|&(old, _)| old <= ptr.0) - 1];
+			unsafe {
+				(
+					new.offset(ptr.0.offset_from(old)),
+					new.offset(ptr.1.offset_from(old)),
+				)
+			}
+		};
+		let rebase_all = |ptrs: &Vec<ClausePtr>| ptrs.iter().copied().map(rebase).collect();
+		Solver {
+			n: self.n,
+			clauses: self.clauses.iter().copied().map(rebase).collect(),
+			learned: self
+				.learned
+				.iter()
+				.zip(learned_body)
+				.map(|(c, body)| Learned {
+					body,
+					ptr: rebase(c.ptr),
+					lbd: c.lbd,
+				})
+				.collect(),
+			clause_body,
+			next_reduce: self.next_reduce,
+			watchers: self.watchers.iter().map(rebase_all).collect(),
+			binary: self
+				.binary
+				.iter()
+				.map(|b| b.iter().map(|&(lit, ptr)| (lit, rebase(ptr))).collect())
+				.collect(),
+			units: self.units.clone(),
+			unsat: self.unsat,
+			empty: self.empty,
+			eliminated: self.eliminated.clone(),
+			original: self.original.clone(),
+			assign: self.assign.clone(),
+			levels: self.levels.clone(),
+			reasons: self.reasons.iter().map(|r| r.map(rebase)).collect(),
+			level: self.level,
+			trail: self.trail.clone(),
+			qhead: self.qhead,
+			out_of_order: self.out_of_order,
+			seen: self.seen.clone(),
+			frozen: self.frozen.clone(),
+			suggest: self.suggest.clone(),
+			activity: self.activity.clone(),
+			var_inc: self.var_inc,
+			var_decay: self.var_decay,
+			minimization: self.minimization,
+			phase_saving: self.phase_saving,
+			rephase: self.rephase,
+			rephases: self.rephases,
+			rephase_countdown: self.rephase_countdown,
+			best_phase: self.best_phase.clone(),
+			best_trail: self.best_trail,
+			restart_base: self.restart_base,
+			restart_strategy: self.restart_strategy,
+			reduce_strategy: self.reduce_strategy,
+			clause_activity: self
+				.clause_activity
+				.iter()
+				.map(|(&ptr, &activity)| (rebase(ptr), activity))
+				.collect(),
+			clause_inc: self.clause_inc,
+			clause_decay: self.clause_decay,
+			restarts: self.restarts,
+			restart_conflicts: self.restart_conflicts,
+			lbd_fast: self.lbd_fast,
+			lbd_slow: self.lbd_slow,
+			random_var_freq: self.random_var_freq,
+			rng: self.rng,
+			chrono_threshold: self.chrono_threshold,
+			inprocessing: self.inprocessing,
+			conflicts: self.conflicts,
+			decisions: self.decisions,
+			conflict_budget: self.conflict_budget,
+			timeout: self.timeout,
+			propagations: self.propagations,
+			traversals: self.traversals,
+			relocations: self.relocations,
+			total_restarts: self.total_restarts,
+			max_trail: self.max_trail,
+			total_learned: self.total_learned,
+			reductions: self.reductions,
+			deleted_learned: self.deleted_learned,
+			order: self.order.clone(),
+			status: self.status,
+			assumptions: self.assumptions.clone(),
+			assumption_stack: self.assumption_stack.clone(),
+			failed: self.failed.clone(),
+			core_tracking: self.core_tracking,
+			cores: self
+				.cores
+				.iter()
+				.map(|(&ptr, core)| (rebase(ptr), core.as_ref().map(rebase_all)))
+				.collect(),
+			unit_cores: self
+				.unit_cores
+				.iter()
+				.map(|(&lit, core)| (lit, core.as_ref().map(rebase_all)))
+				.collect(),
+			final_core: self.final_core.as_ref().map(rebase_all),
+			proof: self.proof.clone(),
+			var_names: self.var_names.clone(),
+			terminate: None,
+			progress: None,
+			decision: None,
+		}
+	}
+}
+
+/// Writes the original clauses of [`Solver::to_clauses`] in the DIMACS CNF
+/// format. The alternate form
+/// `{:#}` also writes the names of the variables as `c var N = NAME`
+/// comments, which [`Solver::from_dimacs_with_names`] reads back.
+impl fmt::Display for Solver {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		if f.alternate() {
+			let mut names: Vec<_> = self.var_names.iter().collect();
+			names.sort_unstable();
+			for (var, name) in names {
+				writeln!(f, "c var {} = {}", var, name)?;
+			}
+		}
+		writeln!(
+			f,
+			"p cnf {} {}",
+			self.n,
+			self.num_original() + self.empty as usize
+		)?;
+		for clause in self.original_clauses() {
+			for lit in clause {
+				write!(f, "{} ", lit)?;
+			}
+			writeln!(f, "0")?;
+		}
+		if self.empty {
+			writeln!(f, "0")?;
+		}
+		Ok(())
+	}
+}
+
+/// The representative literal equivalent to `lit` in the union-find of
+/// [`Solver::detect_equivalences`], compressing the path.
+fn find_root(parent: &mut [Lit], lit: Lit) -> Lit {
+	let mut path = Vec::new();
+	let mut x = lit;
+	loop {
+		let p = parent[x.var()];
+		let next = if x.as_int() > 0 { p } else { -p };
+		if next == x {
+			break;
+		}
+		path.push(x);
+		x = next;
+	}
+	for y in path {
+		parent[y.var()] = if y.as_int() > 0 { x } else { -x };
+	}
+	x
+}
+
+/// The `i`-th (0-based) element of the Luby sequence `1, 1, 2, 1, 1, 2, 4, 1,
+/// ...`.
+fn luby(mut i: usize) -> usize {
+	// Find the finite subsequence of length `2^k - 1` containing `i`.
+	let mut size = 1;
+	let mut k = 0;
+	while size < i + 1 {
+		size = 2 * size + 1;
+		k += 1;
+	}
+	while size - 1 != i {
+		size /= 2;
+		k -= 1;
+		i %= size;
+	}
+	1 << k
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sat_solver_struct_test() {
+		let ss = Solver::new(3, &[vec![1, -2], vec![-1, 2, 3]]).unwrap();
+		assert_eq!(ss.num_vars(), 3);
+		assert_eq!(ss.num_clauses(), 2);
+		assert_eq!(ss.num_learned(), 0);
+		assert_eq!(ss.clause_body[0].0.len(), 5);
+		assert_eq!(ss.clauses.len(), 2);
+		assert_eq!(ss.watchers.len(), 8);
+		assert_eq!(ss.clause_lits(ss.clause_ref(0))[0].get(), 1);
+		assert_eq!(ss.clause_lits(ss.clause_ref(1))[0].get(), -1);
+		assert_eq!(unsafe { *ss.clauses[1].1 }.get(), 3);
+		assert!(Solver::new(3, &[vec![1, 4]]).is_none());
+		assert!(Solver::new(3, &[vec![1, 0]]).is_none());
+		assert_eq!(
+			Solver::new(3, &[vec![1, 2, 1]]).unwrap().to_clauses(),
+			vec![vec![1, 2]]
+		);
+	}
+
+	#[test]
+	fn solve_test() {
+		assert!(Solver::new(3, &[vec![1, -2], vec![-1, 2, 3]])
+			.unwrap()
+			.solve()
+			.is_sat());
+		assert!(
+			Solver::new(2, &[vec![1, 2], vec![1, -2], vec![-1, 2], vec![-1, -2]])
+				.unwrap()
+				.solve()
+				.is_unsat()
+		);
+	}
+
+	#[test]
+	fn model_test() {
+		let clauses = [vec![1, -2], vec![-1, -3], vec![2, 3]];
+		let mut ss = Solver::new(3, &clauses).unwrap();
+		assert_eq!(ss.model(), None);
+		assert!(ss.solve().is_sat());
+		let model = ss.model().unwrap();
+		assert_eq!(model.len(), 3);
+		assert!(clauses.iter().all(|c| c
+			.iter()
+			.any(|&l| model[l.unsigned_abs() as usize - 1] == (l > 0))));
+
+		let mut ss = Solver::new(2, &[vec![1, 2], vec![1, -2], vec![-1, 2], vec![-1, -2]]).unwrap();
+		assert!(ss.solve().is_unsat());
+		assert_eq!(ss.model(), None);
+	}
+
+	#[test]
+	fn highest_var_test() {
+		let n = 5;
+		let n_lit = n as LitInt;
+		let mut ss = Solver::new(n, &[vec![n_lit, 1], vec![n_lit, -1], vec![-n_lit, 4]]).unwrap();
+		assert_eq!(ss.assign.iter().count(), n + 1);
+		assert_eq!(ss.levels.len(), n + 1);
+		assert_eq!(ss.suggest.len(), n + 1);
+		assert!(ss.solve().is_sat());
+		let model = ss.model().unwrap();
+		assert!(model[n - 1]);
+		assert!(model[3]);
+		assert_eq!(
+			unsafe { ss.eval_unchecked(Lit::new(n_lit).unwrap()) },
+			Some(true)
+		);
+	}
+
+	#[test]
+	fn get_loc_test() {
+		let max = LitInt::MAX as usize;
+		let lit = |l| Lit::new(l).unwrap();
+		assert_eq!(lit(LitInt::MAX).get_loc(), 2 * max);
+		assert_eq!(lit(-LitInt::MAX).get_loc(), 2 * max + 1);
+		assert_eq!(lit(1).get_loc(), 2);
+		assert_eq!(lit(-1).get_loc(), 3);
+
+		let n = 1 << 18;
+		let top = n as LitInt;
+		let clauses = vec![
+			vec![top, -1],
+			vec![-top, top - 1],
+			vec![1],
+			vec![-(top - 1), -2],
+		];
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model_value(top), Some(true));
+		assert_eq!(ss.model_value(2), Some(false));
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+	}
+
+	#[test]
+	fn unit_clause_test() {
+		let mut ss = Solver::new(2, &[vec![1], vec![-1, 2]]).unwrap();
+		assert_eq!(ss.units.len(), 1);
+		assert!(ss.watchers.iter().all(|w| w.len() <= 1));
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model(), Some(vec![true, true]));
+		assert_eq!(ss.levels[2], 0);
+
+		assert!(Solver::new(2, &[vec![1], vec![-1, 2], vec![-2]])
+			.unwrap()
+			.solve()
+			.is_unsat());
+		assert!(Solver::new(1, &[vec![1], vec![-1]])
+			.unwrap()
+			.solve()
+			.is_unsat());
+	}
+
+	#[test]
+	fn empty_clause_test() {
+		let mut ss = Solver::new(2, &[vec![1, 2], vec![]]).unwrap();
+		assert_eq!(ss.clauses.len(), 1);
+		assert!(ss.solve().is_unsat());
+		assert_eq!(ss.model(), None);
+		assert!(Solver::new(0, &[vec![]]).unwrap().solve().is_unsat());
+		assert!(Solver::new(2, &[vec![], vec![3]]).is_none());
+		assert!(Solver::new(2, &[vec![], vec![0]]).is_none());
+	}
+
+	#[test]
+	fn analyze_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(
+			5,
+			&[vec![-1, 2], vec![-3, 4], vec![-2, -4, 5], vec![-2, -4, -5]],
+		)
+		.unwrap();
+		ss.level = 1;
+		assert_eq!(ss.assign_and_propagate(lit(1), None), None);
+		ss.level = 2;
+		let conflict = ss.assign_and_propagate(lit(3), None).unwrap();
+		let (mut learned, level) = ss.analyze(conflict);
+		assert_eq!(learned[0], lit(-4));
+		learned.sort();
+		assert_eq!(learned, vec![lit(-4), lit(-2)]);
+		assert_eq!(level, 1);
+		assert!(ss.seen.iter().all(|&s| !s));
+
+		// -2 is implied by -1 and removed.
+		let mut ss =
+			Solver::new(4, &[vec![-1, 2], vec![-3, -1, -2, 4], vec![-3, -1, -2, -4]]).unwrap();
+		ss.level = 1;
+		assert_eq!(ss.assign_and_propagate(lit(1), None), None);
+		ss.level = 2;
+		let conflict = ss.assign_and_propagate(lit(3), None).unwrap();
+		let (learned, level) = ss.analyze(conflict);
+		assert_eq!(learned, vec![lit(-3), lit(-1)]);
+		assert_eq!(level, 1);
+		assert!(ss.seen.iter().all(|&s| !s));
+	}
+
+	#[test]
+	fn learn_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(
+			6,
+			&[
+				vec![-1, 2],
+				vec![-3, 4],
+				vec![-2, -4, 5],
+				vec![-2, -4, -5, -6],
+			],
+		)
+		.unwrap();
+		let mut conflict = None;
+		for (level, l) in [(1, 6), (2, 1), (3, 3)] {
+			ss.level = level;
+			conflict = ss.assign_and_propagate(lit(l), None);
+		}
+		let (learned, level) = ss.analyze(conflict.unwrap());
+		assert_eq!(level, 2);
+		let ptr = ss.learn(learned);
+		// -6 at level 1 and -2 at level 2.
+		assert_eq!(unsafe { (*ptr.0, *ptr.1) }, (lit(-4), lit(-2)));
+		assert!(ss.watchers[lit(4).get_loc()].contains(&ptr));
+		assert!(ss.watchers[lit(2).get_loc()].contains(&ptr));
+		assert_eq!(ss.learned.len(), 1);
+		assert_eq!(ss.learned[0].lbd, 3);
+	}
+
+	#[test]
+	fn backjump_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(4, &[vec![-1, -3, 4], vec![-1, -3, -4], vec![2, 3]]).unwrap();
+		let mut conflict = None;
+		for (level, l) in [(1, 1), (2, 2), (3, 3)] {
+			ss.level = level;
+			conflict = ss.assign_and_propagate(lit(l), None);
+		}
+		let (learned, level) = ss.analyze(conflict.unwrap());
+		assert_eq!(learned[0], lit(-3));
+		assert_eq!(level, 1);
+		let ptr = ss.learn(learned);
+		ss.backjump(level);
+		assert_eq!(ss.level, 1);
+		assert_eq!(ss.trail, vec![lit(1)]);
+		assert_eq!(ss.assign[2], None);
+		assert_eq!(ss.assign_and_propagate(lit(-3), Some(ptr)), None);
+		// -3 at level 1 implies 2 by the last clause.
+		assert_eq!(ss.assign[2], Some(true));
+		assert_eq!(ss.levels[2], 1);
+		assert_eq!(ss.reasons[3], Some(ptr));
+	}
+
+	#[test]
+	fn vsids_test() {
+		let mut ss = Solver::new(3, &[vec![1, 2], vec![2, 3]]).unwrap();
+		ss.set_var_decay(0.5);
+		ss.bump(3);
+		ss.var_inc /= ss.var_decay;
+		ss.bump(2);
+		assert_eq!(ss.activity[1..], [0.0, 2.0, 1.0]);
+		assert_eq!(ss.pick_branch(), Lit::new(-2));
+		ss.activity[1] = 1e100;
+		ss.var_inc = 1e99;
+		ss.bump(1);
+		assert!((ss.activity[1] - 1.1).abs() < 1e-9);
+		assert!(ss.activity[2] < 1e-99 && ss.var_inc < 1.0);
+		assert_eq!(ss.pick_branch(), Lit::new(-1));
+		assert_eq!(ss.pick_branch(), Lit::new(-3));
+		assert_eq!(ss.pick_branch(), None);
+	}
+
+	#[test]
+	fn phase_saving_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(3, &[vec![1, 2, 3]]).unwrap();
+		ss.suggest[2] = true;
+		ss.bump(2);
+		assert_eq!(ss.pick_branch(), Some(lit(2)));
+
+		ss.level = 1;
+		ss.assign_and_propagate(lit(-3), None);
+		ss.backjump(0);
+		assert!(!ss.suggest[3]);
+		ss.level = 1;
+		ss.assign_and_propagate(lit(3), None);
+		ss.backjump(0);
+		assert!(ss.suggest[3]);
+
+		let mut ss = Solver::new(3, &[vec![1, 2, 3]]).unwrap();
+		ss.suggest = vec![false, true, true, true];
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model(), Some(vec![true; 3]));
+	}
+
+	/// A linear congruential generator from the seed, which returns a number
+	/// in `0..m` on each call with `m`, for random inputs which are the same
+	/// in every run.
+	pub(crate) fn lcg(mut seed: u64) -> impl FnMut(u64) -> u64 {
+		move |m| {
+			seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1);
+			(seed >> 33) % m
+		}
+	}
+
+	/// `clauses` random clauses of 3 literals over `n` variables, the same for
+	/// the same seed. With `distinct`, the variables of each clause are
+	/// distinct. Otherwise a literal may be repeated or appear with its
+	/// negation, so that the solver shortens or drops the clause.
+	///
+	/// # Panics
+	///
+	/// Panics if `distinct` is set with fewer than 3 variables.
+	pub(crate) fn random_cnf(
+		seed: u64,
+		n: usize,
+		clauses: usize,
+		distinct: bool,
+	) -> Vec<Vec<LitInt>> {
+		assert!(!distinct || n >= 3 || clauses == 0, "too few variables");
+		let mut next = lcg(seed);
+		(0..clauses)
+			.map(|_| {
+				let mut clause: Vec<LitInt> = Vec::new();
+				while clause.len() < 3 {
+					let var = 1 + next(n as u64) as LitInt;
+					if !distinct || !clause.iter().any(|l| l.abs() == var) {
+						clause.push(if next(2) == 0 { var } else { -var });
+					}
+				}
+				clause
+			})
+			.collect()
+	}
+
+	/// `p` pigeons in `h` holes, which is unsatisfiable iff `p > h`.
+	pub(crate) fn pigeonhole(p: usize, h: usize) -> (usize, Vec<Vec<LitInt>>) {
+		let var = |i: usize, j: usize| (i * h + j + 1) as LitInt;
+		let mut clauses: Vec<Vec<LitInt>> = (0..p)
+			.map(|i| (0..h).map(|j| var(i, j)).collect())
+			.collect();
+		for j in 0..h {
+			for i1 in 0..p {
+				for i2 in i1 + 1..p {
+					clauses.push(vec![-var(i1, j), -var(i2, j)]);
+				}
+			}
+		}
+		(p * h, clauses)
+	}
+
+	#[test]
+	fn luby_test() {
+		let seq: Vec<_> = (0..15).map(luby).collect();
+		assert_eq!(seq, vec![1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+	}
+
+	#[test]
+	fn restart_test() {
+		for &base in &[0, 1, 100] {
+			let (n, clauses) = pigeonhole(6, 5);
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			ss.set_restart_base(base);
+			assert!(ss.solve().is_unsat());
+			let (n, clauses) = pigeonhole(6, 6);
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			ss.set_restart_base(base);
+			assert!(ss.solve().is_sat());
+		}
+	}
+
+	#[test]
+	fn restart_strategy_test() {
+		let strategies = [
+			RestartStrategy::Luby,
+			RestartStrategy::Glucose,
+			RestartStrategy::None,
+		];
+		for &strategy in &strategies {
+			let (n, clauses) = pigeonhole(6, 5);
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			ss.set_restart_strategy(strategy);
+			assert!(ss.solve().is_unsat());
+			assert!(ss.lbd_fast >= 1.0 && ss.lbd_slow >= 1.0);
+		}
+		// Switched between the solves.
+		let (n, clauses) = pigeonhole(7, 7);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		for &strategy in strategies.iter().cycle().take(6) {
+			ss.set_restart_strategy(strategy);
+			assert!(ss.solve_with_assumptions(&[-1, -9, -17]).is_sat());
+			assert!(ss.check_model());
+		}
+	}
+
+	#[test]
+	fn reduce_learned_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(6, &[]).unwrap();
+		for v in 1..=6 {
+			ss.level = v as usize;
+			ss.assign_and_propagate(lit(-v), None);
+		}
+		// LBD 6, 5, 4 and 2.
+		let c1 = ss.learn(vec![lit(1), lit(2), lit(3), lit(4), lit(5), lit(6)]);
+		let c2 = ss.learn(vec![lit(1), lit(2), lit(3), lit(4), lit(5)]);
+		let c3 = ss.learn(vec![lit(1), lit(2), lit(3), lit(4)]);
+		let c4 = ss.learn(vec![lit(1), lit(2)]);
+		// `c2` is the reason of 5.
+		ss.backjump(4);
+		ss.level = 5;
+		ss.assign_and_propagate(lit(5), Some(c2));
+		assert_eq!(ss.num_learned(), 4);
+		ss.reduce_learned();
+		assert_eq!(ss.num_learned(), 3);
+		assert_eq!(ss.num_clauses(), 3);
+		let kept: Vec<_> = ss.learned.iter().map(|c| c.ptr).collect();
+		assert_eq!(kept, vec![c2, c3, c4]);
+		assert!(ss.watchers.iter().flatten().all(|&w| w != c1));
+		assert_eq!(
+			ss.watchers.iter().flatten().filter(|&&w| w == c3).count(),
+			2
+		);
+	}
+
+	#[test]
+	fn reduce_strategy_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(8, &[]).unwrap();
+		let c0 = ss.learn(vec![lit(6), lit(7), lit(8)]);
+		ss.set_reduce_strategy(ReduceStrategy::Activity);
+		assert_eq!(ss.clause_activity[&c0], 0.0);
+		for v in 1..=6 {
+			ss.level = v as usize;
+			ss.assign_and_propagate(lit(-v), None);
+		}
+		// Each one is bumped once when learned.
+		let c1 = ss.learn(vec![lit(1), lit(2), lit(3), lit(4), lit(5), lit(6)]);
+		let c2 = ss.learn(vec![lit(1), lit(2), lit(3), lit(4), lit(5)]);
+		let c3 = ss.learn(vec![lit(1), lit(2), lit(3)]);
+		let c4 = ss.learn(vec![lit(1), lit(2)]);
+		let c5 = ss.learn(vec![lit(1), lit(2), lit(3), lit(4)]);
+		ss.clause_inc = 4.0;
+		ss.bump_clause(c1);
+		ss.bump_clause(c1);
+		ss.bump_clause(c2);
+		// The original clauses have no activity.
+		ss.bump_clause((c1.0, c1.0));
+		assert_eq!(ss.clause_activity[&c1], 9.0);
+		assert_eq!(ss.clause_activity.len(), 6);
+		// `c3` is the reason of 3.
+		ss.backjump(2);
+		ss.level = 3;
+		ss.assign_and_propagate(lit(3), Some(c3));
+		ss.reduce_learned();
+		// `c0`, `c3` and `c4` are the least active half, but `c3` is a reason
+		// and `c4` is binary.
+		let kept: Vec<_> = ss.learned.iter().map(|c| c.ptr).collect();
+		assert_eq!(kept, vec![c1, c2, c3, c4, c5]);
+		assert_eq!(ss.clause_activity.len(), 5);
+		assert!(!ss.clause_activity.contains_key(&c0));
+
+		// The activities rescale instead of overflowing.
+		ss.clause_inc = 2e20;
+		ss.bump_clause(c2);
+		assert!((ss.clause_activity[&c2] - 2.0).abs() < 1e-9);
+		assert!((ss.clause_inc - 2.0).abs() < 1e-9);
+		assert!(ss.clause_activity[&c1] < 1e-18);
+		ss.set_reduce_strategy(ReduceStrategy::Lbd);
+		assert!(ss.clause_activity.is_empty());
+
+		// The increment grows by the inverse of the decay on each conflict.
+		let (n, clauses) = pigeonhole(5, 4);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		ss.set_reduce_strategy(ReduceStrategy::Activity);
+		ss.set_clause_decay(0.5);
+		ss.set_conflict_budget(Some(10));
+		assert_eq!(ss.solve(), SolveResult::Unknown);
+		assert_eq!(ss.clause_inc, 2f64.powi(ss.conflicts() as i32));
+		assert_eq!(ss.clone().clause_decay, 0.5);
+
+		for strategy in [ReduceStrategy::Lbd, ReduceStrategy::Activity] {
+			let (n, clauses) = pigeonhole(8, 7);
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			ss.set_reduce_strategy(strategy);
+			ss.next_reduce = 100;
+			assert!(ss.solve().is_unsat());
+			assert!(ss.reductions > 0);
+			let (n, clauses) = pigeonhole(7, 7);
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			ss.set_reduce_strategy(strategy);
+			assert!(ss.solve().is_sat());
+		}
+	}
+
+	#[test]
+	fn add_clause_test() {
+		let mut ss = Solver::new(3, &[vec![1, 2]]).unwrap();
+		assert_eq!(ss.add_clause(&[1, 0]), Err(ClauseError::ZeroLiteral));
+		assert_eq!(ss.add_clause(&[1, -4]), Err(ClauseError::OutOfRange(-4)));
+		ss.add_clause(&[2, 3, 2]).unwrap();
+		assert_eq!(ss.clause_lits(ss.clause_ref(1)).len(), 2);
+		ss.add_clause(&[-1]).unwrap();
+		// Propagated at the level 0 immediately.
+		assert_eq!(ss.assign[2], Some(true));
+		ss.add_clause(&[3, -2]).unwrap();
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model(), Some(vec![false, true, true]));
+
+		let first = ss.clauses[0];
+		for _ in 0..100 {
+			ss.add_clause(&[1, 2, 3]).unwrap();
+		}
+		assert_eq!(
+			Solver::lits(first),
+			&[Lit::new(1).unwrap(), Lit::new(2).unwrap()]
+		);
+		ss.add_clause(&[-3, 1]).unwrap();
+		assert_eq!(ss.model(), None);
+		assert!(ss.solve().is_unsat());
+
+		let mut ss = Solver::new(2, &[vec![1, 2]]).unwrap();
+		ss.add_clause(&[]).unwrap();
+		assert!(ss.solve().is_unsat());
+	}
+
+	#[test]
+	fn assumption_test() {
+		let mut ss = Solver::new(4, &[vec![1, 2], vec![-1, 3]]).unwrap();
+		assert!(ss.solve_with_assumptions(&[-3]).is_sat());
+		assert_eq!(ss.model().unwrap()[..3], [false, true, false]);
+		assert!(ss.solve_with_assumptions(&[4, -2, -3]).is_unsat());
+		let mut failed = ss.failed_assumptions();
+		failed.sort();
+		assert_eq!(failed, vec![-3, -2]);
+		assert_eq!(ss.model(), None);
+		assert!(ss.solve_with_assumptions(&[1, -1]).is_unsat());
+		assert_eq!(ss.failed_assumptions().len(), 2);
+		// The assumptions are not kept.
+		assert!(ss.solve().is_sat());
+		assert!(ss.failed_assumptions().is_empty());
+
+		let mut ss = Solver::new(2, &[vec![1], vec![-1, 2]]).unwrap();
+		assert!(ss.solve_with_assumptions(&[1, -2]).is_unsat());
+		assert_eq!(ss.failed_assumptions(), vec![-2]);
+		ss.add_clause(&[-1]).unwrap();
+		assert!(ss.solve_with_assumptions(&[2]).is_unsat());
+		assert!(ss.failed_assumptions().is_empty());
+	}
+
+	/// Checks that each clause added in the DRAT proof is implied by unit
+	/// propagation (RUP), and returns whether the proof has the empty clause.
+	fn check_rup(clauses: &[Vec<LitInt>], proof: &str) -> bool {
+		let mut db: Vec<Vec<LitInt>> = clauses.to_vec();
+		for line in proof.lines() {
+			let (deleted, line) = match line.strip_prefix("d ") {
+				Some(line) => (true, line),
+				None => (false, line),
+			};
+			let mut clause: Vec<LitInt> = line
+				.split_whitespace()
+				.map(|t| t.parse().unwrap())
+				.collect();
+			assert_eq!(clause.pop(), Some(0));
+			if deleted {
+				// Literals may be reordered by propagation.
+				let mut sorted = clause.clone();
+				sorted.sort_unstable();
+				let i = db
+					.iter()
+					.position(|c| {
+						let mut c = c.clone();
+						c.sort_unstable();
+						c == sorted
+					})
+					.unwrap();
+				db.swap_remove(i);
+				continue;
+			}
+			let mut assign: Vec<LitInt> = clause.iter().map(|&l| -l).collect();
+			let rup = loop {
+				let mut changed = false;
+				let mut conflict = false;
+				for c in &db {
+					let open: Vec<_> = c.iter().filter(|l| !assign.contains(&-**l)).collect();
+					if c.iter().any(|l| assign.contains(l)) {
+						continue;
+					}
+					match open.len() {
+						0 => conflict = true,
+						1 => {
+							assign.push(*open[0]);
+							changed = true;
+						}
+						_ => {}
+					}
+				}
+				if conflict || !changed {
+					break conflict;
+				}
+			};
+			assert!(rup, "{:?} is not RUP", clause);
+			if clause.is_empty() {
+				return true;
+			}
+			db.push(clause);
+		}
+		false
+	}
+
+	#[test]
+	fn proof_test() {
+		let (n, clauses) = pigeonhole(4, 3);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		let mut out = Vec::new();
+		assert!(ss.solve_with_proof(&mut out).unwrap().is_unsat());
+		let proof = String::from_utf8(out).unwrap();
+		assert!(proof.ends_with("\n0\n"));
+		assert!(check_rup(&clauses, &proof));
+		assert!(ss.proof.is_none());
+
+		let (n, clauses) = pigeonhole(4, 4);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		let mut out = Vec::new();
+		assert!(ss.solve_with_proof(&mut out).unwrap().is_sat());
+		assert!(!check_rup(&clauses, &String::from_utf8(out).unwrap()));
+	}
+
+	#[test]
+	fn clauses_test() {
+		let clauses = vec![vec![1, -2], vec![-1, 2, 3], vec![2]];
+		let mut ss = Solver::new(3, &clauses).unwrap();
+		ss.add_clause(&[-3, 1]).unwrap();
+		let as_int = |c: &[Lit]| {
+			let mut c: Vec<_> = c.iter().map(|l| l.get()).collect();
+			c.sort();
+			c
+		};
+		let got: Vec<_> = ss.clauses().map(as_int).collect();
+		assert_eq!(got, vec![vec![-2, 1], vec![-1, 2, 3], vec![2], vec![-3, 1]]);
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.clauses().count(), 4);
+		assert_eq!(ss.learned_clauses().count(), ss.num_learned());
+	}
+
+	#[test]
+	fn tautology_test() {
+		let mut ss = Solver::new(3, &[vec![1, -1, 2], vec![-2, 3], vec![-3]]).unwrap();
+		assert_eq!(ss.num_clauses(), 2);
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model(), Some(vec![false, false, false]));
+		let ss = unsafe { Solver::new_unchecked(3, &[vec![1, -1, 2], vec![-2, 3]]) };
+		assert_eq!(ss.num_clauses(), 1);
+
+		let mut ss = Solver::new(2, &[vec![-1]]).unwrap();
+		ss.add_clause(&[2, 1, -2]).unwrap();
+		assert_eq!(ss.num_clauses(), 1);
+	}
+
+	#[test]
+	fn fmt_test() {
+		let mut ss = Solver::new(3, &[vec![1, -2], vec![2, 3], vec![-1]]).unwrap();
+		assert_eq!(ss.to_string(), "p cnf 3 3\n1 -2 0\n2 3 0\n-1 0\n");
+		assert_eq!(
+			format!("{:?}", ss),
+			"Solver { n: 3, clauses: 3, level: 0, assign: [] }"
+		);
+		assert!(ss.solve().is_sat());
+		let debug = format!("{:?}", ss);
+		assert!(debug.contains("assign: [-1, -2, 3]"), "{}", debug);
+		let ss = Solver::new(2, &[vec![1], vec![]]).unwrap();
+		assert_eq!(ss.to_string(), "p cnf 2 2\n1 0\n0\n");
+		let text = Solver::new(2, &[vec![1, 2], vec![-2]]).unwrap().to_string();
+		assert_eq!(Solver::from_dimacs(&text).unwrap().to_string(), text);
+	}
+
+	#[test]
+	fn reset_assignment_test() {
+		let (n, clauses) = pigeonhole(4, 4);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		assert!(ss.solve().is_sat());
+		assert!(ss.solve_with_assumptions(&[1, 5]).is_unsat());
+		ss.reset_assignment();
+		assert_eq!(ss.level, 0);
+		assert!(ss.trail.is_empty());
+		assert!(ss.assign.iter().all(|v| v.is_none()));
+		assert_eq!(ss.model(), None);
+		let watched = clauses.iter().filter(|c| c.len() > 1).count() + ss.num_learned();
+		let count = ss.watchers.iter().map(Vec::len).sum::<usize>()
+			+ ss.binary.iter().map(Vec::len).sum::<usize>();
+		assert_eq!(count, 2 * watched);
+		for (i, w) in ss.watchers.iter().enumerate() {
+			let binary = ss.binary[i].iter().map(|&(_, ptr)| ptr);
+			for (first, last) in w.iter().copied().chain(binary) {
+				// The clauses in `watchers[i]` watch the negation of the literal
+				// at `i`.
+				let var = (i / 2) as LitInt;
+				let lit = if i % 2 == 1 { var } else { -var };
+				assert!(unsafe { first.read().get() == lit || last.read().get() == lit });
+			}
+		}
+		assert!(ss.solve().is_sat());
+		let model = ss.model().unwrap();
+		assert!(clauses.iter().all(|c| c
+			.iter()
+			.any(|&l| model[l.unsigned_abs() as usize - 1] == (l > 0))));
+	}
+
+	#[test]
+	fn conflict_budget_test() {
+		let (n, clauses) = pigeonhole(7, 6);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		ss.set_conflict_budget(Some(10));
+		assert_eq!(ss.solve(), SolveResult::Unknown);
+		assert_eq!(ss.conflicts(), 10);
+		assert!(ss.decisions() >= 10);
+		assert_eq!(ss.level, 0);
+		assert_eq!(ss.model(), None);
+		let mut rounds = 1;
+		while ss.solve() == SolveResult::Unknown {
+			rounds += 1;
+		}
+		assert!(rounds > 2);
+		assert!(ss.solve().is_unsat());
+		ss.set_conflict_budget(Some(0));
+		assert!(ss.solve().is_unsat());
+
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		assert!(ss.solve().is_unsat());
+		assert!(ss.conflicts() > 10);
+	}
+
+	#[test]
+	fn stats_test() {
+		let mut ss = Solver::new(3, &[vec![-1, 2, 3], vec![-1, -2]]).unwrap();
+		assert_eq!(ss.stats(), SolverStats::default());
+		ss.assign_and_propagate(Lit::new(1).unwrap(), None);
+		// 1 implies -2 by the binary clause first, so that the watch of the
+		// first clause cannot move from -1 to 2, and it implies 3.
+		assert_eq!(ss.stats().propagations, 3);
+		assert_eq!(ss.stats().traversals, 3);
+		assert_eq!(ss.stats().relocations, 0);
+
+		let (n, clauses) = pigeonhole(5, 4);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		assert!(ss.solve().is_unsat());
+		let stats = ss.stats();
+		assert_eq!(stats.conflicts, ss.conflicts());
+		assert_eq!(stats.decisions, ss.decisions());
+		assert!(stats.propagations >= stats.decisions);
+		assert!(stats.traversals > 0 && stats.relocations > 0);
+		assert_eq!(stats.restarts, ss.restarts());
+		assert_eq!(stats.propagations, ss.propagations());
+		assert_eq!(stats.total_learned, stats.conflicts);
+		assert!(stats.max_trail > 0 && stats.max_trail <= n);
+
+		// The counters add up across the solves until they are reset.
+		let (n, clauses) = pigeonhole(8, 7);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		ss.set_restart_base(10);
+		ss.set_conflict_budget(Some(50));
+		assert_eq!(ss.solve(), SolveResult::Unknown);
+		let first = ss.stats();
+		assert!(first.restarts > 0);
+		assert_eq!(first.total_learned, 50);
+		assert_eq!(ss.solve(), SolveResult::Unknown);
+		let second = ss.stats();
+		assert_eq!(second.conflicts, 100);
+		assert!(second.restarts > first.restarts);
+		assert!(second.total_learned == 100 && second.learned <= 100);
+		assert!(second.max_trail >= first.max_trail);
+		ss.reset_stats();
+		let reset = ss.stats();
+		assert_eq!(
+			reset,
+			SolverStats {
+				learned: second.learned,
+				trail: 0,
+				..SolverStats::default()
+			}
+		);
+		assert_eq!(ss.solve(), SolveResult::Unknown);
+		assert_eq!(ss.conflicts(), 50);
+		let mut ss = Solver::new(2, &[vec![1], vec![-1, 2]]).unwrap();
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.max_trail(), 2);
+		assert_eq!(ss.total_learned(), 0);
+	}
+
+	#[test]
+	fn propagate_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss =
+			Solver::new(4, &[vec![-1, 2], vec![-2, 3], vec![-3, -4], vec![-2, 4, 3]]).unwrap();
+		ss.level = 1;
+		ss.set(lit(1), None);
+		assert_eq!(ss.propagate(), None);
+		assert_eq!(ss.trail, vec![lit(1), lit(2), lit(3), lit(-4)]);
+		assert_eq!(ss.qhead, 4);
+		assert_eq!(ss.levels[4], 1);
+		assert_eq!(ss.reasons[2], Some(ss.clauses[0]));
+		assert_eq!(ss.reasons[4], Some(ss.clauses[2]));
+		ss.level = 2;
+		ss.backjump(0);
+		ss.set(lit(-3), None);
+		ss.set(lit(1), None);
+		// -3 implies -2, which falsifies the first clause with 1.
+		assert_eq!(ss.propagate(), Some(ss.clauses[0]));
+		assert_eq!(ss.qhead, ss.trail.len());
+	}
+
+	#[test]
+	fn simplify_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(4, &[vec![1], vec![-1, 2], vec![-2, 3, 4], vec![-3]]).unwrap();
+		assert!(ss.simplify());
+		assert_eq!(ss.trail, vec![lit(1), lit(-3), lit(2), lit(4)]);
+		assert!(ss.trail.iter().all(|l| ss.levels[l.var()] == 0));
+		assert!(ss.simplify());
+		assert_eq!(ss.trail.len(), 4);
+
+		let mut ss = Solver::new(2, &[vec![1], vec![2], vec![-1]]).unwrap();
+		assert!(!ss.simplify());
+		assert!(ss.unsat);
+
+		let mut ss = Solver::new(2, &[vec![1, 2]]).unwrap();
+		ss.add_clause(&[-2]).unwrap();
+		assert_eq!(ss.assign[1], Some(true));
+		ss.add_clause(&[2]).unwrap();
+		assert!(ss.unsat);
+		assert!(ss.solve().is_unsat());
+	}
+
+	#[test]
+	fn pure_literal_test() {
+		let mut ss = Solver::new(
+			5,
+			&[
+				vec![1, -2],
+				vec![1, 3],
+				vec![-3, 2, 4],
+				vec![-4, -2],
+				vec![5],
+			],
+		)
+		.unwrap();
+		ss.eliminate_pure_literals();
+		// 1 is pure, 5 is a unit, and the others appear in both polarities.
+		assert_eq!(ss.assign[1], Some(true));
+		assert_eq!(ss.assign[2], None);
+		assert_eq!(ss.assign[5], Some(true));
+		assert_eq!(ss.num_clauses(), 3);
+		assert_eq!(ss.watchers.iter().map(Vec::len).sum::<usize>(), 2);
+		assert_eq!(ss.binary.iter().map(Vec::len).sum::<usize>(), 2);
+		assert!(ss.solve().is_sat());
+		let model = ss.model().unwrap();
+		assert!(model[0] && model[4]);
+		// The pure literal stays after the reset.
+		ss.reset_assignment();
+		assert!(ss.solve_with_assumptions(&[-1]).is_unsat());
+		assert_eq!(ss.failed_assumptions(), vec![-1]);
+
+		let (n, clauses) = pigeonhole(5, 4);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		ss.eliminate_pure_literals();
+		assert_eq!(ss.num_clauses(), clauses.len());
+		assert!(ss.solve().is_unsat());
+
+		// The reasons at the level 0 are kept.
+		let mut ss = Solver::new(3, &[vec![1], vec![-1, 2], vec![2, 3]]).unwrap();
+		ss.eliminate_pure_literals();
+		assert_eq!(ss.reason_of(2), Some(ss.clause_ref(1)));
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+	}
+
+	#[test]
+	fn model_value_test() {
+		let mut ss = Solver::new(3, &[vec![1, -2], vec![-1], vec![2, 3]]).unwrap();
+		assert_eq!(ss.model_value(1), None);
+		assert!(ss.solve().is_sat());
+		let model = ss.model().unwrap();
+		for var in 1..=3 {
+			assert_eq!(ss.model_value(var), Some(model[var as usize - 1]));
+			assert_eq!(ss.model_value(-var), Some(!model[var as usize - 1]));
+		}
+	}
+
+	#[test]
+	fn timeout_test() {
+		let (n, clauses) = pigeonhole(8, 7);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		ss.set_timeout(Some(Duration::ZERO));
+		assert_eq!(ss.solve(), SolveResult::Unknown);
+		assert!(ss.conflicts() >= CHECK_INTERVAL);
+		assert!(ss.conflicts() < 2 * CHECK_INTERVAL);
+		// The conflict budget trips first.
+		ss.set_conflict_budget(Some(10));
+		let conflicts = ss.conflicts();
+		assert_eq!(ss.solve(), SolveResult::Unknown);
+		assert_eq!(ss.conflicts(), conflicts + 10);
+		ss.set_conflict_budget(None);
+		ss.set_timeout(Some(Duration::from_secs(3600)));
+		assert!(ss.solve().is_unsat());
+	}
+
+	#[test]
+	fn terminate_callback_test() {
+		use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+		use std::sync::Arc;
+		let (n, clauses) = pigeonhole(9, 8);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		let stop = Arc::new(AtomicBool::new(false));
+		let polls = Arc::new(AtomicUsize::new(0));
+		{
+			let (stop, polls) = (stop.clone(), polls.clone());
+			ss.set_terminate_callback(move || {
+				polls.fetch_add(1, Ordering::Relaxed);
+				stop.load(Ordering::Relaxed)
+			});
+		}
+		ss.set_conflict_budget(Some(3 * CHECK_INTERVAL));
+		assert_eq!(ss.solve(), SolveResult::Unknown);
+		assert_eq!(polls.load(Ordering::Relaxed), 2);
+		stop.store(true, Ordering::Relaxed);
+		ss.set_conflict_budget(None);
+		assert_eq!(ss.solve(), SolveResult::Unknown);
+		assert_eq!(polls.load(Ordering::Relaxed), 3);
+		ss.clear_terminate_callback();
+		ss.set_conflict_budget(Some(2 * CHECK_INTERVAL));
+		assert_eq!(ss.solve(), SolveResult::Unknown);
+		assert_eq!(polls.load(Ordering::Relaxed), 3);
+	}
+
+	#[test]
+	fn solve_all_test() {
+		// Exactly one of 1, 2 and 3, and 4 is free.
+		let mut ss = Solver::new(
+			4,
+			&[vec![1, 2, 3], vec![-1, -2], vec![-2, -3], vec![-1, -3]],
+		)
+		.unwrap();
+		let mut models = Vec::new();
+		assert_eq!(
+			ss.solve_all(|model| {
+				models.push(model.to_vec());
+				true
+			}),
+			SolveResult::Unsat
+		);
+		models.sort();
+		assert_eq!(models.len(), 6);
+		models.dedup();
+		assert_eq!(models.len(), 6);
+		assert!(models
+			.iter()
+			.all(|m| m[..3].iter().filter(|&&b| b).count() == 1));
+
+		let mut ss = Solver::new(3, &[]).unwrap();
+		let mut count = 0;
+		assert_eq!(
+			ss.solve_all(|_| {
+				count += 1;
+				count < 3
+			}),
+			SolveResult::Sat
+		);
+		assert_eq!(count, 3);
+		assert_eq!(ss.num_clauses(), 2);
+	}
+
+	#[test]
+	fn count_models_projected_test() {
+		// 3 is 1 or 2, and 4 is free.
+		let clauses = [vec![-3, 1, 2], vec![3, -1], vec![3, -2]];
+		let mut ss = Solver::new(4, &clauses).unwrap();
+		assert_eq!(ss.count_models_projected(&[1, 2, 3, 4]), 8);
+		let mut ss = Solver::new(4, &clauses).unwrap();
+		assert_eq!(ss.count_models_projected(&[3, 1, 3]), 3);
+		let mut ss = Solver::new(4, &clauses).unwrap();
+		assert_eq!(ss.count_models_projected(&[]), 1);
+		assert_eq!(ss.count_models_projected(&[1]), 0);
+		let mut ss = Solver::new(1, &[vec![1], vec![-1]]).unwrap();
+		assert_eq!(ss.count_models_projected(&[1]), 0);
+	}
+
+	#[test]
+	fn level_of_test() {
+		let mut ss = Solver::new(3, &[vec![1], vec![-1, 2, 3]]).unwrap();
+		assert_eq!(ss.value(1), None);
+		assert_eq!(ss.level_of(1), None);
+		assert!(ss.solve_with_assumptions(&[-2, -3]).is_unsat());
+		assert_eq!(ss.model_value(1), None);
+		assert_eq!(ss.value(1), Some(true));
+		assert_eq!(ss.level_of(1), Some(0));
+		assert_eq!(ss.value(2), None);
+		assert_eq!(ss.level_of(2), None);
+		assert!(ss.solve_with_assumptions(&[-2]).is_sat());
+		assert_eq!(ss.value(2), Some(false));
+		assert_eq!(ss.level_of(2), Some(1));
+		assert_eq!(ss.value(3), Some(true));
+		assert_eq!(ss.level_of(3), Some(1));
+	}
+
+	#[test]
+	fn add_xor_test() {
+		let mut ss = Solver::new(3, &[]).unwrap();
+		ss.add_xor(&[1, 2, 3], true).unwrap();
+		assert_eq!(ss.num_clauses(), 4);
+		let mut count = 0;
+		ss.solve_all(|model| {
+			assert_eq!(model.iter().filter(|&&b| b).count() % 2, 1);
+			count += 1;
+			true
+		});
+		assert_eq!(count, 4);
+
+		// 1 XOR -2 is true iff 1 and 2 are equal.
+		let mut ss = Solver::new(3, &[vec![1]]).unwrap();
+		ss.add_xor(&[1, -2], true).unwrap();
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model_value(2), Some(true));
+		ss.add_xor(&[3, -3], false).unwrap();
+		assert!(ss.solve().is_unsat());
+
+		let mut ss = Solver::new(2, &[]).unwrap();
+		ss.add_xor(&[], false).unwrap();
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.add_xor(&[1, 3], true), Err(ClauseError::OutOfRange(3)));
+		assert_eq!(
+			ss.add_xor(&[1, 1], true),
+			Err(ClauseError::DuplicateLiteral(1))
+		);
+		ss.add_xor(&[], true).unwrap();
+		assert!(ss.solve().is_unsat());
+	}
+
+	#[test]
+	fn add_at_most_k_test() {
+		for (k, expected) in [(0, 1), (1, 5), (2, 11), (3, 15), (4, 16)] {
+			let mut ss = Solver::new(4, &[]).unwrap();
+			let aux = ss.add_at_most_k(&[1, 2, 3, 4], k).unwrap();
+			assert_eq!(aux.len(), if k < 4 { 3 * k } else { 0 });
+			assert_eq!(ss.num_vars(), 4 + aux.len());
+			assert_eq!(ss.count_models_projected(&[1, 2, 3, 4]), expected);
+		}
+
+		// At most one of -1, 2 and 3, with 1 false.
+		let mut ss = Solver::new(3, &[vec![-1]]).unwrap();
+		let aux = ss.add_at_most_k(&[-1, 2, 3], 1).unwrap();
+		assert_eq!(aux, vec![4, 5]);
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model().unwrap()[..3], [false, false, false]);
+		ss.add_clause(&[2, 3]).unwrap();
+		assert!(ss.solve().is_unsat());
+		assert_eq!(
+			ss.add_at_most_k(&[1, 9], 1),
+			Err(ClauseError::OutOfRange(9))
+		);
+	}
+
+	#[test]
+	fn try_new_test() {
+		let ss = Solver::try_new(3, &[vec![1, -2], vec![2, -2], vec![3]]).unwrap();
+		assert_eq!(ss.num_clauses(), 2);
+		let error = Solver::try_new(3, &[vec![1], vec![2, 4]]).unwrap_err();
+		assert_eq!(
+			error,
+			BuildError {
+				clause: 1,
+				error: ClauseError::OutOfRange(4)
+			}
+		);
+		assert_eq!(error.to_string(), "clause 1: literal 4 out of range");
+		assert_eq!(
+			Solver::try_new(3, &[vec![0], vec![1, 1]])
+				.unwrap_err()
+				.error,
+			ClauseError::ZeroLiteral
+		);
+		assert_eq!(
+			Solver::try_new(3, &[vec![], vec![1, 1]])
+				.unwrap()
+				.to_clauses(),
+			vec![vec![1], vec![]]
+		);
+
+		// A repeated literal is kept once, and a tautology is dropped even
+		// with a repeated literal.
+		let ss = Solver::try_new(3, &[vec![1, 1, 2], vec![1, -1, 2], vec![3, 1, 3, -1]]).unwrap();
+		assert_eq!(ss.to_clauses(), vec![vec![1, 2]]);
+		let mut ss = Solver::new(3, &[]).unwrap();
+		ss.add_clauses(&[vec![-2, 3, -2, 3], vec![2, 2, -2]])
+			.unwrap();
+		assert_eq!(ss.to_clauses(), vec![vec![-2, 3]]);
+		assert_eq!(
+			ss.add_xor(&[1, 1], true),
+			Err(ClauseError::DuplicateLiteral(1))
+		);
+	}
+
+	#[test]
+	fn random_var_freq_test() {
+		let (n, clauses) = pigeonhole(6, 5);
+		let run = |freq: Option<f64>, seed: u64| {
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			if let Some(freq) = freq {
+				ss.set_random_var_freq(freq);
+			}
+			ss.set_seed(seed);
+			assert!(ss.solve().is_unsat());
+			(ss.conflicts(), ss.decisions())
+		};
+		// No random number is drawn with `0.0`.
+		assert_eq!(run(Some(0.0), 1), run(None, 2));
+		assert_eq!(run(Some(0.5), 7), run(Some(0.5), 7));
+		assert_eq!(run(Some(1.0), 0), run(Some(1.0), 0));
+
+		let (n, clauses) = pigeonhole(6, 6);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		ss.set_random_var_freq(1.0);
+		assert!(ss.solve().is_sat());
+	}
+
+	#[test]
+	fn conflict_graph_dot_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(5, &[vec![-1, 2], vec![-2, -3, 4], vec![-2, -3, -4]]).unwrap();
+		assert_eq!(ss.conflict_graph_dot(), None);
+		ss.level = 1;
+		assert_eq!(ss.assign_and_propagate(lit(5), None), None);
+		ss.level = 2;
+		assert_eq!(ss.assign_and_propagate(lit(1), None), None);
+		ss.level = 3;
+		assert!(ss.assign_and_propagate(lit(3), None).is_some());
+		let dot = ss.conflict_graph_dot().unwrap();
+		assert!(dot.starts_with("digraph conflict {\n"));
+		// 5 does not lead to the conflict.
+		assert!(!dot.contains("\"5\""));
+		assert!(dot.contains("  \"1\" [label=\"1 @ 2\", shape=box];\n"));
+		assert!(dot.contains("  \"2\" [label=\"2 @ 2\", shape=ellipse];\n"));
+		assert!(dot.contains("  \"1\" -> \"2\";\n"));
+		assert!(dot.contains("  \"3\" -> \"4\";\n"));
+		assert!(dot.contains("  \"4\" -> conflict;\n"));
+		assert!(dot.contains("  \"3\" -> conflict;\n"));
+		assert!(dot.ends_with("}\n"));
+
+		let mut ss = Solver::new(2, &[vec![1], vec![-1, 2], vec![-2]]).unwrap();
+		assert!(ss.solve().is_unsat());
+		assert!(ss
+			.conflict_graph_dot()
+			.unwrap()
+			.contains("conflict [color=red"));
+		let mut ss = Solver::new(2, &[vec![1, 2]]).unwrap();
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.conflict_graph_dot(), None);
+	}
+
+	#[test]
+	fn new_var_test() {
+		let mut ss = Solver::new(2, &[vec![1, 2], vec![-1, 2]]).unwrap();
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.new_var(), 3);
+		assert_eq!(ss.num_vars(), 3);
+		ss.add_clause(&[-2, 3]).unwrap();
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model(), Some(vec![ss.model().unwrap()[0], true, true]));
+		// The watchers of the old variables are moved.
+		assert_eq!(ss.new_var(), 4);
+		ss.add_clause(&[-3, -4]).unwrap();
+		ss.add_clause(&[4, -1]).unwrap();
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model(), Some(vec![false, true, true, false]));
+		ss.add_clause(&[1]).unwrap();
+		assert!(ss.solve().is_unsat());
+
+		let mut ss = Solver::new(0, &[]).unwrap();
+		let x = ss.new_var();
+		ss.add_clause(&[-x]).unwrap();
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model_value(x), Some(false));
+	}
+
+	#[test]
+	fn clause_ref_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(3, &[vec![-1, 2], vec![1, 3]]).unwrap();
+		let clause = ss.clause_ref(0);
+		assert!(!clause.is_learned());
+		assert_eq!(ss.clause_lits(clause), ss.clauses().next().unwrap());
+		ss.level = 1;
+		assert_eq!(ss.assign_and_propagate(lit(1), None), None);
+		assert_eq!(ss.reason_of(1), None);
+		assert_eq!(ss.reason_of(2), Some(clause));
+		assert_eq!(ss.reason_of(3), None);
+
+		// A learned clause as a reason.
+		let mut ss = Solver::new(3, &[vec![-1, 2, 3], vec![-1, 2, -3]]).unwrap();
+		ss.level = 1;
+		assert!(ss.assign_and_propagate(lit(1), None).is_none());
+		ss.level = 2;
+		let conflict = ss.assign_and_propagate(lit(-2), None).unwrap();
+		let (learned, level) = ss.analyze(conflict);
+		let ptr = ss.learn(learned);
+		ss.backjump(level);
+		assert!(ss.assign_and_propagate(lit(2), Some(ptr)).is_none());
+		let reason = ss.reason_of(2).unwrap();
+		assert!(reason.is_learned());
+		assert_eq!(ss.clause_lits(reason), ss.learned_clauses().next().unwrap());
+	}
+
+	#[test]
+	fn watched_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(4, &[vec![1, 2, 3], vec![-1, 4], vec![2]]).unwrap();
+		let clause = ss.clause_ref(0);
+		assert_eq!(ss.watched(clause), (lit(1), lit(3)));
+		assert_eq!(ss.watchers_of(1), vec![clause]);
+		assert_eq!(ss.watchers_of(-1), vec![ss.clause_ref(1)]);
+		assert_eq!(ss.watchers_of(2), vec![]);
+		assert_eq!(ss.watched(ss.clause_ref(2)), (lit(2), lit(2)));
+		ss.level = 1;
+		assert_eq!(ss.assign_and_propagate(lit(-1), None), None);
+		// The watch on -1 moves to 2.
+		let (first, last) = ss.watched(clause);
+		assert_eq!((first, last), (lit(2), lit(3)));
+		assert_eq!(ss.watchers_of(1), vec![]);
+		assert_eq!(ss.watchers_of(2), vec![clause]);
+		// Each clause is watched by two literals which are not false.
+		for i in 0..2 {
+			let (first, last) = ss.watched(ss.clause_ref(i));
+			for l in [first, last] {
+				assert_ne!(unsafe { ss.eval_unchecked(l) }, Some(false));
+				assert!(ss.watchers_of(l.get()).contains(&ss.clause_ref(i)));
+			}
+		}
+	}
+
+	#[cfg(feature = "debug-invariants")]
+	#[test]
+	#[should_panic(expected = "broken invariant: clause [1, 2, 3] is not watched once by 1")]
+	fn debug_invariants_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(3, &[vec![1, 2, 3]]).unwrap();
+		ss.level = 1;
+		// The lost watch is found by the first propagation.
+		ss.watchers[lit(-1).get_loc()].clear();
+		ss.assign_and_propagate(lit(2), None);
+	}
+
+	#[test]
+	fn verify_watch_invariant_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let (n, clauses) = pigeonhole(5, 4);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+		ss.level = 1;
+		assert_eq!(ss.assign_and_propagate(lit(1), None), None);
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+		assert!(ss.solve().is_unsat());
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+
+		let mut ss = Solver::new(3, &[vec![1, 2, 3], vec![-1, 2]]).unwrap();
+		let ptr = ss.watchers[lit(-1).get_loc()].pop().unwrap();
+		assert_eq!(
+			ss.verify_watch_invariant(),
+			Err("clause [1, 2, 3] is not watched once by 1".to_string())
+		);
+		ss.watchers[lit(-2).get_loc()].push(ptr);
+		assert!(ss.verify_watch_invariant().is_err());
+
+		// A watched literal made false without propagation.
+		let mut ss = Solver::new(3, &[vec![1, 2, 3]]).unwrap();
+		ss.set(lit(-1), None);
+		ss.qhead = 1;
+		assert_eq!(
+			ss.verify_watch_invariant(),
+			Err("clause [1, 2, 3] watches false 1 with 3 not true".to_string())
+		);
+		ss.qhead = 0;
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+		ss.assign.set(2, Some(true));
+		assert!(ss.verify_watch_invariant().is_err());
+	}
+
+	#[test]
+	fn check_model_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(3, &[vec![1, 2], vec![-1, 3]]).unwrap();
+		assert!(!ss.check_model());
+		assert!(ss.solve().is_sat());
+		assert!(ss.check_model());
+		ss.backjump(0);
+		ss.level = 1;
+		ss.set(lit(1), None);
+		ss.set(lit(-3), None);
+		assert!(!ss.check_model());
+
+		let (n, clauses) = pigeonhole(4, 4);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		assert!(ss.solve().is_sat());
+		assert!(ss.check_model());
+		ss.add_clause(&[]).unwrap();
+		assert!(!ss.check_model());
+
+		// The eliminated clauses are checked against the extended model.
+		let mut ss = Solver::new(2, &[vec![1, 2], vec![1, -2]]).unwrap();
+		ss.eliminate_vars(0);
+		assert_eq!(ss.num_clauses(), 0);
+		assert_eq!(ss.to_clauses(), vec![vec![1, 2], vec![1, -2]]);
+		assert!(ss.solve().is_sat());
+		assert!(ss.check_model());
+		ss.eliminated.clear();
+		assert!(!ss.check_model());
+	}
+
+	#[test]
+	#[should_panic(expected = "1 is decided at the level 0")]
+	fn check_unsat_trail_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(2, &[vec![-1, 2], vec![-1, -2]]).unwrap();
+		// A spurious assignment at the level 0 is caught.
+		let conflict = ss.assign_and_propagate(lit(1), None).unwrap();
+		ss.check_unsat_trail(conflict);
+	}
+
+	#[test]
+	fn eliminate_blocked_clauses_test() {
+		// [1, 2] is blocked by 1, since the resolvent with [-1, -2, 3] is a
+		// tautology, and then the others are blocked too.
+		let clauses = vec![vec![1, 2], vec![-1, -2, 3], vec![-3, 4], vec![2, -4]];
+		let mut ss = Solver::new(4, &clauses).unwrap();
+		ss.eliminate_blocked_clauses();
+		assert!(ss.num_clauses() < clauses.len());
+		assert!(ss.solve().is_sat());
+		let model = ss.model().unwrap();
+		for clause in &clauses {
+			assert!(clause
+				.iter()
+				.any(|&l| model[l.unsigned_abs() as usize - 1] == (l > 0)));
+			assert!(clause.iter().any(|&l| ss.model_value(l) == Some(true)));
+		}
+
+		// Nothing is blocked.
+		let (n, clauses) = pigeonhole(4, 3);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		ss.eliminate_blocked_clauses();
+		assert_eq!(ss.num_clauses(), clauses.len());
+		assert!(ss.solve().is_unsat());
+
+		// A unit clause and the reason at the level 0 are kept.
+		let mut ss = Solver::new(3, &[vec![1], vec![-1, 2], vec![-2, 3, 1]]).unwrap();
+		ss.eliminate_blocked_clauses();
+		assert_eq!(ss.num_clauses(), 2);
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model(), Some(vec![true, true, ss.model().unwrap()[2]]));
+
+		// Random 3-SAT, whose models must satisfy the whole formula.
+		let mut next = lcg(777);
+		for _ in 0..200 {
+			let n = 1 + next(10) as usize;
+			let clauses = random_cnf(next(1 << 32), n, next(5 * n as u64) as usize, false);
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			let expected = ss.solve_naive();
+			ss.eliminate_blocked_clauses();
+			assert_eq!(ss.solve().is_sat(), expected, "{:?}", clauses);
+			if let Some(model) = ss.model() {
+				for clause in &clauses {
+					assert!(clause
+						.iter()
+						.any(|&l| model[l.unsigned_abs() as usize - 1] == (l > 0)));
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn eliminate_vars_test() {
+		// 2 is resolved out: [1, 3] replaces [1, 2] and [-2, 3].
+		let clauses = vec![vec![1, 2], vec![-2, 3], vec![-1, -3], vec![-1, 3]];
+		let mut ss = Solver::new(3, &clauses).unwrap();
+		ss.eliminate_vars(0);
+		assert!(ss.clauses().all(|c| c.iter().all(|l| l.var() != 2)));
+		assert!(ss.solve().is_sat());
+		let model = ss.model().unwrap();
+		assert_eq!(model, vec![false, true, true]);
+
+		let (n, clauses) = pigeonhole(4, 3);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		ss.eliminate_vars(0);
+		assert!(ss.solve().is_unsat());
+
+		// Random 3-SAT, whose models must satisfy the whole formula.
+		let mut next = lcg(4242);
+		for i in 0..300 {
+			let n = 1 + next(10) as usize;
+			let clauses = random_cnf(next(1 << 32), n, next(5 * n as u64) as usize, false);
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			let expected = ss.solve_naive();
+			ss.eliminate_vars(i % 3);
+			if i % 2 == 0 {
+				ss.eliminate_blocked_clauses();
+			}
+			assert_eq!(ss.solve().is_sat(), expected, "{:?}", clauses);
+			if let Some(model) = ss.model() {
+				for clause in &clauses {
+					assert!(
+						clause
+							.iter()
+							.any(|&l| model[l.unsigned_abs() as usize - 1] == (l > 0)),
+						"{:?}",
+						clauses
+					);
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn chronological_test() {
+		let mut next = lcg(4321);
+		let mut instances = vec![pigeonhole(6, 5), pigeonhole(6, 6)];
+		for _ in 0..40 {
+			let n = 20 + next(40) as usize;
+			let clauses = random_cnf(next(1 << 32), n, n * 43 / 10, false);
+			instances.push((n, clauses));
+		}
+		let mut out_of_order = false;
+		for (n, clauses) in instances {
+			let mut plain = Solver::new(n, &clauses).unwrap();
+			let mut chrono = Solver::new(n, &clauses).unwrap();
+			chrono.set_chronological(true);
+			// Backtrack chronologically on every conflict.
+			chrono.chrono_threshold = Some(0);
+			let expected = plain.solve();
+			assert_eq!(chrono.solve(), expected);
+			out_of_order |= chrono.out_of_order;
+			if expected.is_sat() {
+				assert!(chrono.check_model());
+			}
+			let assumptions = [1, -2, 3];
+			let expected = plain.solve_with_assumptions(&assumptions);
+			assert_eq!(chrono.solve_with_assumptions(&assumptions), expected);
+			assert_eq!(chrono.verify_watch_invariant(), Ok(()));
+		}
+		assert!(out_of_order);
+	}
+
+	#[test]
+	fn add_clauses_test() {
+		let mut ss = Solver::new(3, &[vec![1, 2]]).unwrap();
+		assert!(ss.solve().is_sat());
+		// Nothing is added before the bad clause.
+		assert_eq!(
+			ss.add_clauses(&[vec![-1], vec![2, 4], vec![-2]]),
+			Err(ClauseError::OutOfRange(4))
+		);
+		assert_eq!(ss.num_clauses(), 1);
+		assert!(ss.model().is_some());
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+
+		ss.add_clauses(&[vec![-1], vec![1, -1], vec![-2, 3, 1]])
+			.unwrap();
+		assert_eq!(ss.num_clauses(), 3);
+		assert_eq!(ss.clause_body.len(), 2);
+		// Propagated at the level 0 in order.
+		assert_eq!(ss.assign[2], Some(true));
+		assert_eq!(ss.assign[3], Some(true));
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+		assert!(ss.solve().is_sat());
+		ss.add_clauses(&[vec![-3, 2], vec![]]).unwrap();
+		assert!(ss.solve().is_unsat());
+	}
+
+	#[test]
+	fn inprocess_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(5, &[vec![1, 2], vec![4]]).unwrap();
+		// Subsumed by [1, 2].
+		ss.learn(vec![lit(1), lit(2), lit(3)]);
+		let c2 = ss.learn(vec![lit(3), lit(-5)]);
+		ss.learn(vec![lit(-5), lit(1), lit(3)]);
+		ss.learn(vec![lit(3), lit(-5)]);
+		// Satisfied by 4.
+		ss.learn(vec![lit(4), lit(5)]);
+		let c6 = ss.learn(vec![lit(-3), lit(5)]);
+		assert!(ss.inprocess());
+		let kept: Vec<_> = ss.learned.iter().map(|c| c.ptr).collect();
+		assert_eq!(kept, vec![c2, c6]);
+		assert_eq!(ss.assign[4], Some(true));
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+
+		let mut ss = Solver::new(2, &[vec![1, 2], vec![-1, 2], vec![-2]]).unwrap();
+		assert!(!ss.inprocess());
+	}
+
+	#[test]
+	fn inprocessing_test() {
+		let mut next = lcg(777);
+		let mut instances = vec![pigeonhole(6, 5), pigeonhole(7, 7)];
+		for _ in 0..30 {
+			let n = 20 + next(60) as usize;
+			let clauses = random_cnf(next(1 << 32), n, n * 43 / 10, false);
+			instances.push((n, clauses));
+		}
+		for (n, clauses) in instances {
+			let mut plain = Solver::new(n, &clauses).unwrap();
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			ss.set_inprocessing(true);
+			ss.set_restart_base(1);
+			let expected = plain.solve();
+			assert_eq!(ss.solve(), expected);
+			assert!(!expected.is_sat() || ss.check_model());
+			assert_eq!(
+				ss.solve_with_assumptions(&[1, -2]),
+				plain.solve_with_assumptions(&[1, -2])
+			);
+		}
+	}
+
+	#[test]
+	fn subsume_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let clauses = vec![
+			vec![1, 2, 3],
+			vec![1, 2],
+			vec![-1, 3, 4],
+			vec![2, 1],
+			vec![-1, 4, 5, 3],
+			vec![5],
+			vec![5, -2],
+		];
+		let mut ss = Solver::new(5, &clauses).unwrap();
+		ss.learn(vec![lit(-1), lit(4)]);
+		ss.learn(vec![lit(3), lit(-1), lit(4), lit(2)]);
+		ss.learn(vec![lit(1), lit(2), lit(-4)]);
+		let c4 = ss.learn(vec![lit(-3), lit(2)]);
+		ss.learn(vec![lit(-3), lit(2)]);
+		// [1, 2, 3], [2, 1], [-1, 4, 5, 3] and [5, -2], the second and the
+		// third learned clause, and the duplicate of `c4`. The learned [-1, 4]
+		// does not remove the original [-1, 3, 4].
+		assert_eq!(ss.subsume(), 7);
+		let ints: Vec<Vec<LitInt>> = ss
+			.clauses()
+			.map(|c| c.iter().map(|l| l.get()).collect())
+			.collect();
+		assert_eq!(ints, vec![vec![1, 2], vec![-1, 3, 4], vec![5]]);
+		assert_eq!(ss.learned.len(), 2);
+		assert_eq!(ss.learned[1].ptr, c4);
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+		assert_eq!(ss.subsume(), 0);
+		assert!(ss.solve().is_sat());
+		assert!(ss.check_model());
+	}
+
+	#[test]
+	fn detect_equivalences_test() {
+		// 2 = 1 and 3 = -2.
+		let clauses = vec![
+			vec![-1, 2],
+			vec![1, -2],
+			vec![2, 3],
+			vec![-2, -3],
+			vec![-3, -4, 5],
+			vec![3, 4],
+			vec![2, -5, 6],
+		];
+		let mut ss = Solver::new(6, &clauses).unwrap();
+		ss.detect_equivalences();
+		let ints: Vec<Vec<LitInt>> = ss
+			.clauses()
+			.map(|c| {
+				let mut c: Vec<_> = c.iter().map(|l| l.get()).collect();
+				c.sort_unstable_by_key(|lit| lit.abs());
+				c
+			})
+			.collect();
+		assert_eq!(ints.len(), 3);
+		assert!(ints.contains(&vec![1, -4, 5]));
+		assert!(ints.contains(&vec![-1, 4]));
+		assert!(ints.contains(&vec![1, -5, 6]));
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+		for unit in [1, -1] {
+			let mut ss = Solver::new(6, &clauses).unwrap();
+			ss.detect_equivalences();
+			ss.add_clause(&[unit]).unwrap();
+			assert!(ss.solve().is_sat());
+			let model = ss.model().unwrap();
+			assert_eq!(model[1], model[0]);
+			assert_eq!(model[2], !model[0]);
+			assert!(clauses.iter().all(|c| c
+				.iter()
+				.any(|&l| model[l.unsigned_abs() as usize - 1] == (l > 0))));
+		}
+
+		// 1 = 2 and 1 = -2.
+		let mut ss = Solver::new(2, &[vec![-1, 2], vec![1, -2], vec![1, 2], vec![-1, -2]]).unwrap();
+		ss.detect_equivalences();
+		assert!(ss.solve().is_unsat());
+	}
+
+	#[test]
+	fn probe_test() {
+		// No literal leads to a conflict.
+		let clauses = vec![vec![-1, 2], vec![1, 2, 4], vec![-2, 3], vec![-2, -3, 4]];
+		let mut ss = Solver::new(4, &clauses).unwrap();
+		assert_eq!(ss.probe(), 0);
+		let clauses = vec![vec![1, 2], vec![-2, 3], vec![-2, -3], vec![-1, 4, 5]];
+		let mut ss = Solver::new(5, &clauses).unwrap();
+		// -1 fails by [1, 2], and then 2 fails.
+		assert_eq!(ss.probe(), 2);
+		assert_eq!(ss.trail, vec![Lit::new(1).unwrap(), Lit::new(-2).unwrap()]);
+		assert_eq!(ss.probe(), 0);
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model_value(2), Some(false));
+		assert_eq!(ss.model_value(1), Some(true));
+
+		// Both 1 and -1 fail.
+		let clauses = vec![vec![1, 2], vec![1, -2], vec![-1, 3], vec![-1, -3]];
+		let mut ss = Solver::new(3, &clauses).unwrap();
+		ss.probe();
+		assert!(ss.unsat);
+		assert!(ss.solve().is_unsat());
+
+		// Random 3-SAT, probed and then solved.
+		let mut next = lcg(4321);
+		for _ in 0..200 {
+			let n = 1 + next(10) as usize;
+			let clauses = random_cnf(next(1 << 32), n, next(5 * n as u64) as usize, false);
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			let expected = ss.solve_naive();
+			ss.probe();
+			assert_eq!(ss.solve().is_sat(), expected, "{:?}", clauses);
+			if let Some(model) = ss.model() {
+				for clause in &clauses {
+					assert!(clause
+						.iter()
+						.any(|&l| model[l.unsigned_abs() as usize - 1] == (l > 0)));
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn determinism_test() {
+		let n = 60;
+		let clauses = random_cnf(2024, n, 250, true);
+		let run = |configure: &dyn Fn(&mut Solver)| {
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			configure(&mut ss);
+			// The added clauses are allocated at other addresses in each run.
+			ss.add_clause(&[1, 2, 3]).unwrap();
+			let result = ss.solve();
+			let learned: Vec<Vec<Lit>> = ss.learned_clauses().map(<[Lit]>::to_vec).collect();
+			(result, ss.model(), ss.stats(), learned)
+		};
+		let configs: [&dyn Fn(&mut Solver); 3] = [
+			&|_| {},
+			&|ss| {
+				ss.set_restart_strategy(RestartStrategy::Glucose);
+				ss.set_chronological(true);
+				ss.set_inprocessing(true);
+			},
+			&|ss| {
+				ss.set_random_var_freq(0.1);
+				ss.set_seed(7);
+			},
+		];
+		for configure in configs {
+			let first = run(configure);
+			assert!(first.2.conflicts > 0);
+			assert_eq!(run(configure), first);
+		}
+	}
+
+	#[test]
+	fn into_clauses_test() {
+		let clauses = vec![vec![1, -2], vec![3], vec![-1, 2, -3]];
+		let mut ss = Solver::new(3, &clauses).unwrap();
+		assert_eq!(ss.to_clauses(), clauses);
+		ss.add_clause(&[2, 3]).unwrap();
+		assert!(ss.solve().is_sat());
+		let restored = Solver::new(3, &ss.to_clauses()).unwrap();
+		assert_eq!(restored.to_string(), ss.to_string());
+		ss.add_clause(&[]).unwrap();
+		let (n, clauses) = ss.into_clauses();
+		assert_eq!(n, 3);
+		assert_eq!(clauses.last(), Some(&vec![]));
+		assert!(Solver::new(n, &clauses).unwrap().solve().is_unsat());
+	}
+
+	#[test]
+	fn trail_test() {
+		let mut ss = Solver::new(3, &[vec![2], vec![-1, 3], vec![-2, -3, 1]]).unwrap();
+		assert_eq!(ss.trail(), vec![]);
+		assert!(ss.simplify());
+		assert_eq!(ss.trail(), vec![(2, 0)]);
+		ss.level = 1;
+		assert!(ss
+			.assign_and_propagate(Lit::new(-1).unwrap(), None)
+			.is_none());
+		assert_eq!(ss.trail(), vec![(2, 0), (-1, 1), (-3, 1)]);
+		ss.backjump(0);
+		assert_eq!(ss.trail(), vec![(2, 0)]);
+	}
+
+	#[test]
+	fn set_polarities_test() {
+		let mut ss = Solver::new(3, &[vec![1, 2, 3]]).unwrap();
+		ss.suggest[3] = true;
+		assert_eq!(ss.set_polarities(&[Some(true), Some(false), None]), Ok(()));
+		assert_eq!(ss.suggest, vec![false, true, false, true]);
+		let err = ss.set_polarities(&[Some(false)]).unwrap_err();
+		assert_eq!(
+			err,
+			PolarityError {
+				expected: 3,
+				found: 1
+			}
+		);
+		assert_eq!(err.to_string(), "1 polarities for 3 variables");
+		assert_eq!(ss.suggest, vec![false, true, false, true]);
+
+		// The decisions follow the polarities.
+		let mut ss = Solver::new(3, &[]).unwrap();
+		ss.set_polarities(&[Some(true), Some(false), Some(true)])
+			.unwrap();
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model(), Some(vec![true, false, true]));
+	}
+
+	#[test]
+	fn minimization_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		// 1 implies 2 and 3 at the level 1, and 4 implies 5, which conflicts
+		// with [-1, -4, -5] at the level 2.
+		let clauses = vec![
+			vec![-1, 2],
+			vec![-2, 3],
+			vec![-3, -4, 5],
+			vec![-3, -4, -5],
+			vec![-1, -4, -5],
+		];
+		let learned = |mode| {
+			let mut ss = Solver::new(5, &clauses).unwrap();
+			ss.set_ccmin_mode(mode);
+			ss.level = 1;
+			assert!(ss.assign_and_propagate(lit(1), None).is_none());
+			ss.level = 2;
+			let conflict = ss.assign_and_propagate(lit(4), None).unwrap();
+			let (mut learned, _) = ss.analyze(conflict);
+			learned[1..].sort();
+			assert!(ss.seen.iter().all(|&seen| !seen));
+			learned
+		};
+		// -3 is implied by -1 through -2, which is not in the clause.
+		assert_eq!(learned(Minimization::None), vec![lit(-4), lit(-3), lit(-1)]);
+		assert_eq!(
+			learned(Minimization::Basic),
+			vec![lit(-4), lit(-3), lit(-1)]
+		);
+		assert_eq!(learned(Minimization::Deep), vec![lit(-4), lit(-1)]);
+	}
+
+	#[test]
+	fn phase_saving_mode_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let run = |mode| {
+			let mut ss = Solver::new(3, &[vec![-1, 2]]).unwrap();
+			ss.set_phase_saving(mode);
+			ss.level = 1;
+			ss.assign_and_propagate(lit(1), None);
+			ss.level = 2;
+			ss.assign_and_propagate(lit(3), None);
+			ss.backjump(0);
+			ss.suggest
+		};
+		assert_eq!(run(PhaseSaving::None), vec![false; 4]);
+		assert_eq!(run(PhaseSaving::Limited), vec![false, false, false, true]);
+		assert_eq!(run(PhaseSaving::Full), vec![false, true, true, true]);
+	}
+
+	#[test]
+	fn progress_callback_test() {
+		use std::cell::RefCell;
+		use std::rc::Rc;
+		let (n, clauses) = pigeonhole(6, 5);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		let log = Rc::new(RefCell::new(Vec::new()));
+		{
+			let log = log.clone();
+			ss.set_progress_callback(move |stats| log.borrow_mut().push(*stats));
+		}
+		ss.set_restart_base(10);
+		assert!(ss.solve().is_unsat());
+		let log = log.borrow();
+		assert!(log.len() > 1);
+		assert!(log.windows(2).all(|w| w[0].conflicts < w[1].conflicts));
+		assert!(log.iter().all(|stats| stats.conflicts <= ss.conflicts()));
+		assert!(log.iter().any(|stats| stats.learned > 0));
+		assert_eq!(ss.stats().learned, ss.num_learned());
+		ss.clear_progress_callback();
+		assert!(ss.progress.is_none());
+	}
+
+	#[test]
+	fn unsat_core_test() {
+		let index = |core: Vec<ClauseRef>| core.iter().map(|c| c.index).collect::<Vec<_>>();
+		let clauses = vec![
+			vec![1, 2],
+			vec![-2, 3],
+			vec![4, 5],
+			vec![-4, 5],
+			vec![-1, 4],
+		];
+		let mut ss = Solver::new(5, &clauses).unwrap();
+		ss.set_core_tracking(true);
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.unsat_core(), vec![]);
+		assert!(ss.solve_with_assumptions(&[-1, -3]).is_unsat());
+		assert_eq!(index(ss.unsat_core()), vec![0, 1]);
+		// Without the tracking, all the clauses are returned.
+		ss.set_core_tracking(false);
+		assert!(ss.solve_with_assumptions(&[-1, -3]).is_unsat());
+		assert_eq!(ss.unsat_core().len(), 5);
+
+		// The pigeonhole formula with clauses of other variables, and the unit
+		// clauses of the pigeons in the last holes.
+		let (n, mut clauses) = pigeonhole(5, 4);
+		let extra = clauses.len();
+		clauses.extend(vec![vec![21, 22], vec![-21, 22], vec![-22, 23]]);
+		let mut ss = Solver::new(n + 3, &clauses).unwrap();
+		ss.set_core_tracking(true);
+		ss.set_restart_base(5);
+		assert!(ss.solve().is_unsat());
+		let core = index(ss.unsat_core());
+		assert!(core.iter().all(|&i| i < extra));
+		let core: Vec<_> = core.iter().map(|&i| clauses[i].clone()).collect();
+		assert!(Solver::new(n, &core).unwrap().solve().is_unsat());
+		// The core stays with the unsatisfiable formula.
+		assert!(ss.solve().is_unsat());
+		assert_eq!(ss.unsat_core().len(), core.len());
+
+		// Random 3-SAT with assumptions, whose cores must be unsatisfiable
+		// with the failed assumptions.
+		let mut next = lcg(99);
+		for _ in 0..200 {
+			let n = 3 + next(10) as usize;
+			// The core refers the clauses by their indices, which tautologies
+			// would shift.
+			let clauses = random_cnf(next(1 << 32), n, next(6 * n as u64) as usize, true);
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			ss.set_core_tracking(true);
+			ss.set_restart_base(3);
+			ss.set_chronological(next(2) == 0);
+			let assumptions: Vec<LitInt> = (1..=next(3) as LitInt)
+				.map(|var| if next(2) == 0 { var } else { -var })
+				.collect();
+			if !ss.solve_with_assumptions(&assumptions).is_unsat() {
+				continue;
+			}
+			let mut core: Vec<_> = index(ss.unsat_core())
+				.iter()
+				.map(|&i| clauses[i].clone())
+				.collect();
+			core.extend(ss.failed_assumptions().iter().map(|&lit| vec![lit]));
+			assert!(
+				Solver::new(n, &core).unwrap().solve().is_unsat(),
+				"{:?} {:?}",
+				clauses,
+				assumptions
+			);
+		}
+	}
+
+	#[test]
+	fn clone_test() {
+		let (n, clauses) = pigeonhole(6, 5);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		ss.add_clause(&[1, 2, 3]).unwrap();
+		ss.set_core_tracking(true);
+		ss.set_conflict_budget(Some(50));
+		assert_eq!(ss.solve(), SolveResult::Unknown);
+		assert!(ss.num_learned() > 0);
+		let lit = |l| Lit::new(l).unwrap();
+		ss.level = 1;
+		assert!(ss.assign_and_propagate(lit(1), None).is_none());
+		let mut cloned = ss.clone();
+		assert_eq!(cloned.to_string(), ss.to_string());
+		assert_eq!(cloned.trail, ss.trail);
+		assert_eq!(cloned.verify_watch_invariant(), Ok(()));
+		let end = |s: &Solver| s.clause_body.last().unwrap().base();
+		assert_ne!(end(&cloned), end(&ss));
+		assert!(cloned
+			.reasons
+			.iter()
+			.flatten()
+			.all(|ptr| !ss.clauses.contains(ptr)));
+
+		// The two solvers go separately.
+		let mut free = Solver::new(3, &[vec![1, 2], vec![-1, 3]]).unwrap();
+		assert!(free.solve().is_sat());
+		let mut other = free.clone();
+		other.add_clause(&[-3]).unwrap();
+		other.add_clause(&[-2]).unwrap();
+		assert!(other.solve().is_unsat());
+		assert!(free.solve().is_sat());
+		drop(free);
+		assert!(other.solve().is_unsat());
+
+		drop(ss);
+		cloned.set_conflict_budget(None);
+		assert!(cloned.solve().is_unsat());
+		assert_eq!(cloned.verify_watch_invariant(), Ok(()));
+		let core: Vec<_> = cloned
+			.unsat_core()
+			.into_iter()
+			.map(|c| {
+				cloned
+					.clause_lits(c)
+					.iter()
+					.map(|l| l.get())
+					.collect::<Vec<_>>()
+			})
+			.collect();
+		assert!(Solver::new(n, &core).unwrap().solve().is_unsat());
+	}
+
+	#[test]
+	fn freeze_test() {
+		let clauses = vec![vec![1, 2], vec![-2, 3], vec![-1, -3], vec![-1, 3]];
+		let mut ss = Solver::new(3, &clauses).unwrap();
+		ss.freeze(2);
+		ss.eliminate_vars(0);
+		assert!(ss.clauses().any(|c| c.iter().any(|l| l.var() == 2)));
+		let mut ss = Solver::new(3, &clauses).unwrap();
+		ss.freeze(2);
+		ss.melt(2);
+		ss.eliminate_vars(0);
+		assert!(ss.clauses().all(|c| c.iter().all(|l| l.var() != 2)));
+
+		// 1 is pure, and [1, 2] is blocked by 1 and 2.
+		let mut ss = Solver::new(2, &[vec![1, 2]]).unwrap();
+		ss.freeze(1);
+		ss.freeze(2);
+		ss.eliminate_pure_literals();
+		ss.eliminate_blocked_clauses();
+		assert_eq!(ss.num_clauses(), 1);
+		assert_eq!(ss.value(1), None);
+		assert!(ss.solve_with_assumptions(&[-1, -2]).is_unsat());
+
+		// The frozen variables can be assumed after the eliminations.
+		let mut next = lcg(577);
+		for _ in 0..200 {
+			let n = 2 + next(8) as usize;
+			let clauses = random_cnf(next(1 << 32), n, next(4 * n as u64) as usize, false);
+			let frozen = [1 + next(n as u64) as LitInt, 1 + next(n as u64) as LitInt];
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			for &var in &frozen {
+				ss.freeze(var as usize);
+			}
+			ss.eliminate_pure_literals();
+			ss.eliminate_blocked_clauses();
+			ss.eliminate_vars(1);
+			for bits in 0..4 {
+				let assumptions: Vec<LitInt> = frozen
+					.iter()
+					.enumerate()
+					.map(|(i, &var)| if bits >> i & 1 == 1 { var } else { -var })
+					.collect();
+				let mut with_units = clauses.clone();
+				with_units.extend(assumptions.iter().map(|&lit| vec![lit]));
+				let naive = Solver::new(n, &with_units).unwrap();
+				let expected = naive.solve_naive();
+				assert_eq!(ss.solve_with_assumptions(&assumptions).is_sat(), expected);
+			}
+		}
+	}
+
+	#[test]
+	fn solve_limited_test() {
+		let (n, clauses) = pigeonhole(7, 6);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		let mut slices = 0;
+		let mut resumed = false;
+		while ss.solve_limited(20) == SolveResult::Unknown {
+			slices += 1;
+			assert_eq!(ss.conflicts(), 20 * slices);
+			assert_eq!(ss.model(), None);
+			resumed |= ss.level > 0;
+		}
+		assert!(slices > 2 && resumed);
+		assert!(ss.solve_limited(20).is_unsat());
+		assert_eq!(ss.level, 0);
+
+		// Random 3-SAT, sliced or not.
+		let mut next = lcg(8086);
+		for _ in 0..100 {
+			let n = 10 + next(40) as usize;
+			let clauses = random_cnf(next(1 << 32), n, 4 * n + next(n as u64) as usize, false);
+			let expected = Solver::new(n, &clauses).unwrap().solve();
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			let limit = 1 + next(5);
+			let result = loop {
+				let before = ss.conflicts();
+				match ss.solve_limited(limit) {
+					SolveResult::Unknown => assert_eq!(ss.conflicts(), before + limit),
+					result => break result,
+				}
+			};
+			assert_eq!(result, expected);
+			if result.is_sat() {
+				assert!(ss.check_model());
+			}
+			// A decided solve starts over.
+			assert_eq!(ss.solve_limited(limit), expected);
+		}
+	}
+
+	#[test]
+	fn initial_order_test() {
+		// 1 occurs most, and 3 is in the most short clauses.
+		let clauses = vec![
+			vec![1, 5, 6, 7, 8],
+			vec![1, 9, 10, 11, 12],
+			vec![-1, 5, 9, 6, 10],
+			vec![3, 4],
+			vec![3, -2],
+		];
+		let first = |mode| {
+			let mut ss = Solver::new(12, &clauses).unwrap();
+			ss.set_initial_order(mode);
+			let first = ss.pick_branch().unwrap().var();
+			assert!(ss.solve().is_sat());
+			assert!(ss.check_model());
+			first
+		};
+		assert_eq!(first(InitialOrder::OccurrenceCount), 1);
+		assert_eq!(first(InitialOrder::JeroslowWang), 3);
+
+		// One conflict outweighs the initial order.
+		let mut ss = Solver::new(12, &clauses).unwrap();
+		ss.set_initial_order(InitialOrder::JeroslowWang);
+		assert!(ss.activity.iter().all(|&a| a < ss.var_inc));
+		ss.bump(12);
+		assert_eq!(ss.pick_branch().unwrap().var(), 12);
+		ss.set_initial_order(InitialOrder::None);
+		assert!(ss.activity.iter().all(|&a| a == 0.0));
+
+		let (n, clauses) = pigeonhole(6, 5);
+		for mode in [InitialOrder::OccurrenceCount, InitialOrder::JeroslowWang] {
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			ss.set_initial_order(mode);
+			assert!(ss.solve().is_unsat());
+		}
+	}
+
+	#[test]
+	fn block_current_model_test() {
+		let mut ss = Solver::new(3, &[vec![1, 2], vec![-1, -2, 3]]).unwrap();
+		assert_eq!(ss.block_current_model(), Err(ClauseError::NoModel));
+		let mut models = Vec::new();
+		while ss.solve().is_sat() {
+			models.push(ss.model().unwrap());
+			ss.block_current_model().unwrap();
+			assert_eq!(ss.block_current_model(), Err(ClauseError::NoModel));
+		}
+		assert_eq!(models.len(), 5);
+		models.sort();
+		models.dedup();
+		assert_eq!(models.len(), 5);
+		assert_eq!(ss.block_current_model(), Err(ClauseError::NoModel));
+
+		// The only model of no variables is blocked by the empty clause.
+		let mut ss = Solver::new(0, &[]).unwrap();
+		assert!(ss.solve().is_sat());
+		ss.block_current_model().unwrap();
+		assert!(ss.solve().is_unsat());
+	}
+
+	#[test]
+	fn assumption_stack_test() {
+		let mut ss = Solver::new(4, &[vec![1, 2], vec![-1, 3], vec![-2, 4]]).unwrap();
+		ss.push_assumption(-3);
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model().unwrap()[..4], [false, true, false, true]);
+		ss.push_assumption(-4);
+		assert!(ss.solve().is_unsat());
+		let mut failed = ss.failed_assumptions();
+		failed.sort();
+		assert_eq!(failed, vec![-4, -3]);
+		ss.pop_assumption();
+		assert!(ss.failed_assumptions().is_empty());
+		// The extra assumptions come after the pushed ones.
+		assert!(ss.solve_with_assumptions(&[-2]).is_unsat());
+		assert!(ss.solve_with_assumptions(&[4]).is_sat());
+		assert_eq!(ss.model_value(3), Some(false));
+		ss.pop_assumption();
+
+		// Popping undoes the assumption and its propagations, and keeps the
+		// assignment of the assumptions below it.
+		ss.push_assumption(4);
+		ss.push_assumption(1);
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.assign[3], Some(true));
+		ss.pop_assumption();
+		assert_eq!(ss.model(), None);
+		assert_eq!(ss.assign[4], Some(true));
+		assert_eq!(ss.assign[1], None);
+		assert_eq!(ss.assign[3], None);
+		ss.pop_assumption();
+		assert!(ss.trail.is_empty());
+		assert!(ss.solve().is_sat());
+
+		// Depth-first exploration reuses the learned clauses.
+		let (n, clauses) = pigeonhole(7, 6);
+		let mut ss = Solver::new(n + 1, &clauses).unwrap();
+		ss.push_assumption(n as LitInt + 1);
+		assert!(ss.solve().is_unsat());
+		let learned = ss.stats().learned;
+		assert!(learned > 0);
+		ss.pop_assumption();
+		ss.push_assumption(-(n as LitInt) - 1);
+		assert!(ss.solve_limited(10).is_unsat());
+		ss.pop_assumption();
+		assert!(ss.solve_limited(10).is_unsat());
+	}
+
+	#[test]
+	fn binary_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(4, &[vec![-1, 2], vec![-2, 3], vec![-3, -1, 4]]).unwrap();
+		// The binary clauses are only in `binary`, with the other literal.
+		assert_eq!(ss.watchers.iter().map(Vec::len).sum::<usize>(), 2);
+		assert_eq!(ss.binary[lit(1).get_loc()], vec![(lit(2), ss.clauses[0])]);
+		assert_eq!(ss.binary[lit(-2).get_loc()], vec![(lit(-1), ss.clauses[0])]);
+		assert_eq!(ss.watchers_of(2), vec![ss.clause_ref(0)]);
+		assert_eq!(ss.watchers_of(-3), vec![ss.clause_ref(2)]);
+		ss.level = 1;
+		assert_eq!(ss.assign_and_propagate(lit(1), None), None);
+		assert_eq!(ss.trail, [lit(1), lit(2), lit(3), lit(4)]);
+		assert_eq!(ss.reasons[2], Some(ss.clauses[0]));
+		assert_eq!(ss.reasons[3], Some(ss.clauses[1]));
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+		ss.backjump(0);
+
+		// A conflict on a binary clause is found before the longer clauses.
+		ss.add_clause(&[-1, -3]).unwrap();
+		ss.level = 1;
+		assert_eq!(ss.assign_and_propagate(lit(1), None), Some(ss.clauses[1]));
+		ss.backjump(0);
+		assert!(ss.solve().is_sat());
+		assert!(!ss.model().unwrap()[0]);
+
+		// Learned binary clauses are watched in `binary` too, and cloned.
+		let (n, clauses) = pigeonhole(6, 5);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		ss.set_conflict_budget(Some(200));
+		ss.solve();
+		ss.set_conflict_budget(None);
+		assert_eq!(ss.verify_watch_invariant(), Ok(()));
+		let mut copy = ss.clone();
+		assert_eq!(copy.verify_watch_invariant(), Ok(()));
+		assert!(ss.solve().is_unsat());
+		assert!(copy.solve().is_unsat());
+	}
+
+	#[test]
+	fn solve_diverse_test() {
+		// Without conflicts the decisions alone give the model, which is then
+		// the opposite of `prev` where the clauses allow.
+		let mut ss = Solver::new(4, &[vec![1, 2], vec![-3, -4]]).unwrap();
+		let model = ss.solve_diverse(&[false, false, true, true]).unwrap();
+		assert_eq!(model, vec![true, true, false, false]);
+		// The first decision differs at least.
+		assert_ne!(ss.solve_diverse(&model).unwrap(), model);
+		assert_eq!(ss.num_clauses(), 2);
+
+		let mut ss = Solver::new(2, &[vec![1], vec![-1]]).unwrap();
+		assert_eq!(ss.solve_diverse(&[true, false]), None);
+
+		// Random 3-SAT below the threshold, whose models must be genuine.
+		let n = 30;
+		let clauses = random_cnf(11, n, 90, false);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		let mut prev = vec![false; n];
+		for _ in 0..5 {
+			let model = ss.solve_diverse(&prev).unwrap();
+			assert!(clauses.iter().all(|c| c
+				.iter()
+				.any(|&l| model[l.unsigned_abs() as usize - 1] == (l > 0))));
+			prev = model;
+		}
+	}
+
+	#[test]
+	fn implied_literals_test() {
+		let mut ss = Solver::new(
+			6,
+			&[
+				vec![1],
+				vec![-1, 2],
+				vec![-3, 4],
+				vec![-4, -5],
+				vec![5, 6, 3],
+			],
+		)
+		.unwrap();
+		assert!(ss.implied_literals().is_empty());
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.implied_literals(), vec![1, 2]);
+		assert!(ss.solve_with_assumptions(&[3]).is_sat());
+		assert_eq!(ss.implied_literals(), vec![1, 2, 4, -5]);
+		// An assumption implied by another one is left out too.
+		ss.push_assumption(3);
+		assert!(ss.solve_with_assumptions(&[4, -6]).is_sat());
+		assert_eq!(ss.implied_literals(), vec![1, 2, -5]);
+		assert!(ss.solve_with_assumptions(&[5]).is_unsat());
+		assert!(ss.implied_literals().is_empty());
+	}
+
+	#[test]
+	fn from_clauses_test() {
+		// The chain `x_i -> x_{i+1}` generated lazily.
+		let chain = (1..100).map(|i| vec![-i, i + 1]);
+		let mut ss = Solver::from_clauses(100, chain.chain(Some(vec![1]))).unwrap();
+		assert_eq!(ss.num_clauses(), 100);
+		assert!(ss.solve().is_sat());
+		assert!(ss.model().unwrap().iter().all(|&v| v));
+
+		let clauses = vec![vec![1, 1, 2], vec![-1, 1], vec![], vec![-2]];
+		let ss = Solver::from_clauses(2, &clauses).unwrap();
+		assert_eq!(
+			ss.to_string(),
+			Solver::new(2, &clauses).unwrap().to_string()
+		);
+		assert_eq!(ss.to_string(), "p cnf 2 3\n1 2 0\n-2 0\n0\n");
+		assert_eq!(
+			Solver::from_clauses(2, vec![vec![1], vec![3], vec![0]]).unwrap_err(),
+			BuildError {
+				clause: 1,
+				error: ClauseError::OutOfRange(3),
+			}
+		);
+		let ss = Solver::from_clauses(0, Vec::<Vec<LitInt>>::new()).unwrap();
+		assert_eq!(ss.num_clauses(), 0);
+	}
+
+	#[test]
+	fn normalize_clause_test() {
+		let normalized =
+			|n, clause: &[LitInt]| normalize_clause(n, clause).map(|c| c.map(|c| c.to_vec()));
+		assert_eq!(normalized(3, &[1, -2, 3]), Ok(Some(vec![1, -2, 3])));
+		// A clause without repeated literals is not copied.
+		assert!(matches!(
+			normalize_clause(3, &[3, 1]),
+			Ok(Some(Cow::Borrowed(_)))
+		));
+		assert_eq!(normalized(3, &[]), Ok(Some(vec![])));
+		// The repeated literals are kept at their first occurrence, without
+		// sorting, since the first and the last literal are watched.
+		assert_eq!(normalized(3, &[2, 1, 2, -3, 1]), Ok(Some(vec![2, 1, -3])));
+		assert_eq!(normalized(3, &[2, 2]), Ok(Some(vec![2])));
+		// Tautologies are dropped, with or without repeated literals.
+		assert_eq!(normalized(3, &[1, -1]), Ok(None));
+		assert_eq!(normalized(3, &[2, 1, 2, -2]), Ok(None));
+		assert_eq!(normalized(3, &[1, 0, 2]), Err(ClauseError::ZeroLiteral));
+		assert_eq!(normalized(3, &[1, 1, -4]), Err(ClauseError::OutOfRange(-4)));
+		// Malformed literals are errors even in a tautology.
+		assert_eq!(normalized(3, &[1, -1, 4]), Err(ClauseError::OutOfRange(4)));
+		assert_eq!(normalized(0, &[1]), Err(ClauseError::OutOfRange(1)));
+		assert_eq!(
+			normalized(LitInt::MAX as usize, &[LitInt::MAX, -LitInt::MAX]),
+			Ok(None)
+		);
+	}
+
+	#[test]
+	fn is_unsat_test() {
+		let mut ss = Solver::new(2, &[vec![1], vec![-1]]).unwrap();
+		assert!(!ss.is_unsat());
+		assert!(ss.solve().is_unsat());
+		assert!(ss.is_unsat());
+		// Again without search.
+		assert!(ss.solve_with_assumptions(&[2]).is_unsat());
+		assert!(ss.failed_assumptions().is_empty());
+		assert_eq!(ss.decisions(), 0);
+
+		let mut ss = Solver::new(2, &[vec![1, 2]]).unwrap();
+		assert!(ss.solve_with_assumptions(&[-1, -2]).is_unsat());
+		assert!(!ss.is_unsat());
+		ss.add_clause(&[]).unwrap();
+		assert!(ss.is_unsat());
+		ss.new_var();
+		ss.add_clause(&[3]).unwrap();
+		assert!(ss.is_unsat());
+		assert!(ss.solve().is_unsat());
+
+		// A conflict at the level 0 in add_clause.
+		let mut ss = Solver::new(2, &[vec![1, 2]]).unwrap();
+		ss.add_clause(&[-1]).unwrap();
+		assert!(!ss.is_unsat());
+		ss.add_clause(&[-2]).unwrap();
+		assert!(ss.is_unsat());
+	}
+
+	#[test]
+	fn rephase_test() {
+		let mut ss = Solver::new(3, &[vec![1, 2, 3]]).unwrap();
+		ss.best_phase = vec![false, true, false, true];
+		ss.best_trail = 3;
+		ss.rephase();
+		assert_eq!(ss.suggest[1..], [true; 3]);
+		assert_eq!(ss.rephase_countdown, 2 * REPHASE_INTERVAL);
+		ss.rephase();
+		assert_eq!(ss.suggest[1..], [true, false, true]);
+		assert_eq!(ss.best_trail, 0);
+		ss.rephase();
+		assert_eq!(ss.suggest[1..], [false; 3]);
+		assert_eq!(ss.rephase_countdown, 4 * REPHASE_INTERVAL);
+
+		// The best phase follows the longest trail at a conflict.
+		let (n, clauses) = pigeonhole(7, 6);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		ss.set_rephase(true);
+		ss.set_restart_base(1);
+		assert!(ss.solve().is_unsat());
+		assert!(ss.rephases > 1);
+		assert!(ss.best_phase.iter().any(|&b| b));
+
+		for seed in 0..20 {
+			let clauses = random_cnf(seed, 40, 170, false);
+			let mut ss = Solver::new(40, &clauses).unwrap();
+			ss.set_rephase(true);
+			let result = ss.solve();
+			assert_eq!(result, Solver::new(40, &clauses).unwrap().solve());
+			if let Some(model) = ss.model() {
+				assert!(clauses.iter().all(|c| c
+					.iter()
+					.any(|&l| model[l.unsigned_abs() as usize - 1] == (l > 0))));
+			}
+		}
+	}
+
+	#[test]
+	fn lbd_of_test() {
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(4, &[vec![-1, -2, -3, 4], vec![1, 2]]).unwrap();
+		let clause = ss.clause_ref(0);
+		assert_eq!(ss.lbd_of(clause), 0);
+		ss.level = 1;
+		assert_eq!(ss.assign_and_propagate(lit(1), None), None);
+		assert_eq!(ss.lbd_of(clause), 1);
+		ss.level = 2;
+		assert_eq!(ss.assign_and_propagate(lit(2), None), None);
+		assert_eq!(ss.lbd_of(clause), 2);
+		ss.level = 3;
+		// 4 is implied at the same level as 3.
+		assert_eq!(ss.assign_and_propagate(lit(3), None), None);
+		assert_eq!(unsafe { ss.eval_unchecked(lit(4)) }, Some(true));
+		assert_eq!(ss.lbd_of(clause), 3);
+		assert_eq!(ss.lbd_of(ss.clause_ref(1)), 2);
+
+		// The same as the one recorded for a learned clause when it is derived.
+		let mut ss = Solver::new(3, &[vec![-1, 2, 3], vec![-1, 2, -3]]).unwrap();
+		ss.level = 1;
+		assert!(ss.assign_and_propagate(lit(1), None).is_none());
+		ss.level = 2;
+		let conflict = ss.assign_and_propagate(lit(-2), None).unwrap();
+		let (learned, level) = ss.analyze(conflict);
+		let ptr = ss.learn(learned);
+		let clause = ss.ref_of(ptr);
+		assert_eq!(ss.lbd_of(clause), ss.learned[0].lbd);
+		assert_eq!(ss.lbd_of(clause), 2);
+		ss.backjump(level);
+		assert_eq!(ss.lbd_of(clause), 1);
+	}
+
+	#[test]
+	fn decision_fn_test() {
+		use std::cell::RefCell;
+		use std::rc::Rc;
+		// Deciding the lowest unassigned variable to be true.
+		let lowest = |ss: &Solver| {
+			(1..=ss.num_vars())
+				.find(|&var| ss.value(var).is_none())
+				.map(|var| var as LitInt)
+		};
+		let mut ss = Solver::new(3, &[vec![1, 2, 3], vec![-1, -2], vec![-1, -3, 2]]).unwrap();
+		let log = Rc::new(RefCell::new(Vec::new()));
+		{
+			let log = log.clone();
+			ss.set_decision_fn(move |ss| {
+				let decision = lowest(ss);
+				log.borrow_mut().push(decision);
+				decision
+			});
+		}
+		assert!(ss.solve().is_sat());
+		assert_eq!(ss.model(), Some(vec![true, false, false]));
+		assert_eq!(*log.borrow(), [Some(1), None]);
+
+		// The assumptions come first.
+		log.borrow_mut().clear();
+		assert!(ss.solve_with_assumptions(&[2]).is_sat());
+		// -1 is implied by the assumption.
+		assert_eq!(ss.model(), Some(vec![false, true, true]));
+		assert_eq!(*log.borrow(), [Some(3), None]);
+
+		// `None` falls back to the activities.
+		ss.set_decision_fn(|_| None);
+		assert!(ss.solve().is_sat());
+		ss.clear_decision_fn();
+		assert!(ss.decision.is_none());
+		assert!(ss.clone().solve().is_sat());
+
+		let (n, clauses) = pigeonhole(5, 4);
+		let mut ss = Solver::new(n, &clauses).unwrap();
+		ss.set_decision_fn(lowest);
+		assert!(ss.solve().is_unsat());
+		assert!((1..=n).any(|var| ss.activity(var) > 0.0));
+	}
+
+	#[test]
+	#[should_panic(expected = "bad decision 1")]
+	fn decision_fn_assigned_test() {
+		let mut ss = Solver::new(2, &[vec![1], vec![1, 2]]).unwrap();
+		ss.set_decision_fn(|_| Some(1));
+		ss.solve();
+	}
+
+	#[test]
+	fn warm_restart_test() {
+		// Random 3-SAT near the phase transition, mostly unsatisfiable under
+		// both assumptions, whose second solve is compared with a fresh one.
+		let n = 120;
+		let (first, second) = ([1, 2], [1, -2]);
+		let (mut warm, mut cold) = (0, 0);
+		for seed in 0..10 {
+			let clauses = random_cnf(seed, n, (4.3 * n as f64) as usize, false);
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			ss.solve_with_assumptions(&first);
+			let conflicts = ss.conflicts();
+			let learned = ss.num_learned();
+			let result = ss.solve_with_assumptions(&second);
+			warm += ss.conflicts() - conflicts;
+			let mut fresh = Solver::new(n, &clauses).unwrap();
+			assert_eq!(fresh.solve_with_assumptions(&second), result);
+			cold += fresh.conflicts();
+
+			// Only the heuristics are reset.
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			ss.solve_with_assumptions(&first);
+			ss.reset_heuristics();
+			assert!(ss.activity.iter().all(|&a| a == 0.0));
+			assert!(ss.suggest.iter().all(|&s| !s));
+			assert_eq!(ss.num_learned(), learned);
+			assert_eq!(ss.solve_with_assumptions(&second), result);
+		}
+		assert!(warm < cold, "{} {}", warm, cold);
+	}
+}
@@ -0,0 +1,203 @@
+//! Weighted partial MaxSAT on top of the incremental interface.
+
+use crate::{normalize_clause, ClauseError, LitInt, SolveResult, Solver};
+use std::collections::HashSet;
+
+/// Finds an assignment which satisfies the hard clauses and minimizes the
+/// total weight of the soft clauses it violates.
+///
+/// ```
+/// use yasuosat::MaxSatSolver;
+///
+/// let mut maxsat = MaxSatSolver::new(2);
+/// maxsat.add_hard(&[-1, -2]).unwrap();
+/// maxsat.add_soft(&[1], 3).unwrap();
+/// maxsat.add_soft(&[2], 2).unwrap();
+/// assert_eq!(maxsat.solve_maxsat(), Some((2, vec![true, false])));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MaxSatSolver {
+	n: usize,
+	hard: Vec<Vec<LitInt>>,
+	soft: Vec<(Vec<LitInt>, u64)>,
+}
+
+/// A soft clause in the search, which is enforced by assuming `-selector`.
+struct Soft {
+	/// The literals with the relaxation variables added so far.
+	lits: Vec<LitInt>,
+	weight: u64,
+	selector: LitInt,
+}
+
+impl MaxSatSolver {
+	/// Creates a solver with `n` variables and no clauses.
+	pub fn new(n: usize) -> Self {
+		MaxSatSolver {
+			n,
+			hard: Vec::new(),
+			soft: Vec::new(),
+		}
+	}
+
+	/// Adds the clause which must be satisfied. A tautology is ignored, and a
+	/// repeated literal is kept once.
+	pub fn add_hard(&mut self, clause: &[LitInt]) -> Result<(), ClauseError> {
+		if let Some(clause) = normalize_clause(self.n, clause)? {
+			self.hard.push(clause.into_owned());
+		}
+		Ok(())
+	}
+
+	/// Adds the clause which costs `weight` if it is violated. A tautology and
+	/// a clause of the weight `0` are ignored, and the empty clause always
+	/// costs its weight. A repeated literal is kept once.
+	pub fn add_soft(&mut self, clause: &[LitInt], weight: u64) -> Result<(), ClauseError> {
+		match normalize_clause(self.n, clause)? {
+			Some(clause) if weight > 0 => self.soft.push((clause.into_owned(), weight)),
+			_ => {}
+		}
+		Ok(())
+	}
+
+	/// Returns the minimum total weight of the violated soft clauses and an
+	/// assignment which attains it, or `None` if the hard clauses are
+	/// unsatisfiable.
+	///
+	/// It is the core-guided search of Fu and Malik, weighted as WPM1: while
+	/// the soft clauses are unsatisfiable, each soft clause in the core is
+	/// relaxed by a fresh variable, the relaxation variables of the core are
+	/// constrained to exactly one, and the cost grows by the smallest weight
+	/// in the core, which is split off from the heavier clauses.
+	pub fn solve_maxsat(&self) -> Option<(u64, Vec<bool>)> {
+		let hard: Vec<_> = self.hard.iter().map(|clause| &clause[..]).collect();
+		// The clauses are checked in `add_hard`.
+		let mut solver = unsafe { Solver::build(self.n, &hard) };
+		let mut softs = Vec::with_capacity(self.soft.len());
+		for (clause, weight) in &self.soft {
+			softs.push(Self::enforce(&mut solver, clause.clone(), *weight));
+		}
+		let mut cost = 0;
+		loop {
+			let assumptions: Vec<LitInt> = softs.iter().map(|soft| -soft.selector).collect();
+			match solver.solve_with_assumptions(&assumptions) {
+				SolveResult::Sat => break,
+				SolveResult::Unsat => {}
+				SolveResult::Unknown => unreachable!("no limit is set"),
+			}
+			let failed: HashSet<LitInt> = solver.failed_assumptions().into_iter().collect();
+			if failed.is_empty() {
+				return None;
+			}
+			let core: Vec<usize> = (0..softs.len())
+				.filter(|&i| failed.contains(&-softs[i].selector))
+				.collect();
+			let weight = core.iter().map(|&i| softs[i].weight).min().unwrap();
+			let mut relax = Vec::with_capacity(core.len());
+			for i in core {
+				// The clause with the selector is satisfied forever.
+				solver.add_clause(&[softs[i].selector]).unwrap();
+				let lits = softs[i].lits.clone();
+				if softs[i].weight > weight {
+					let rest = Self::enforce(&mut solver, lits.clone(), softs[i].weight - weight);
+					softs.push(rest);
+				}
+				let b = solver.new_var();
+				let mut relaxed = lits;
+				relaxed.push(b);
+				softs[i] = Self::enforce(&mut solver, relaxed, weight);
+				relax.push(b);
+			}
+			solver.add_clause(&relax).unwrap();
+			solver.add_at_most_k(&relax, 1).unwrap();
+			cost += weight;
+		}
+		let mut model = solver.model().unwrap();
+		model.truncate(self.n);
+		debug_assert_eq!(self.violated(&model), cost);
+		Some((cost, model))
+	}
+
+	/// Adds the soft clause with a fresh selector to the solver.
+	fn enforce(solver: &mut Solver, lits: Vec<LitInt>, weight: u64) -> Soft {
+		let selector = solver.new_var();
+		let mut clause = lits.clone();
+		clause.push(selector);
+		solver.add_clause(&clause).unwrap();
+		Soft {
+			lits,
+			weight,
+			selector,
+		}
+	}
+
+	/// The total weight of the soft clauses violated by the model.
+	fn violated(&self, model: &[bool]) -> u64 {
+		self.soft
+			.iter()
+			.filter(|(clause, _)| {
+				!clause
+					.iter()
+					.any(|&lit| model[lit.unsigned_abs() as usize - 1] == (lit > 0))
+			})
+			.map(|(_, weight)| weight)
+			.sum()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::{lcg, random_cnf};
+
+	#[test]
+	fn maxsat_test() {
+		let mut maxsat = MaxSatSolver::new(3);
+		assert_eq!(maxsat.solve_maxsat(), Some((0, vec![false; 3])));
+		maxsat.add_soft(&[1], 4).unwrap();
+		maxsat.add_soft(&[2], 3).unwrap();
+		maxsat.add_soft(&[3], 2).unwrap();
+		maxsat.add_soft(&[-1, -2], 5).unwrap();
+		maxsat.add_soft(&[], 1).unwrap();
+		maxsat.add_soft(&[-3, 3], 10).unwrap();
+		maxsat.add_hard(&[-2, -3]).unwrap();
+		assert_eq!(maxsat.add_soft(&[4], 1), Err(ClauseError::OutOfRange(4)));
+		// 1 and 3 cost 3 for [2] and 1 for the empty clause.
+		assert_eq!(maxsat.solve_maxsat(), Some((4, vec![true, false, true])));
+		maxsat.add_hard(&[-1]).unwrap();
+		maxsat.add_hard(&[-3]).unwrap();
+		assert_eq!(maxsat.solve_maxsat(), Some((7, vec![false, true, false])));
+		maxsat.add_hard(&[-2]).unwrap();
+		maxsat.add_hard(&[2]).unwrap();
+		assert_eq!(maxsat.solve_maxsat(), None);
+
+		// Random instances against the brute force.
+		let mut next = lcg(31);
+		for _ in 0..100 {
+			let n = 1 + next(8) as usize;
+			let mut maxsat = MaxSatSolver::new(n);
+			for clause in random_cnf(next(1 << 32), n, next(20) as usize, false) {
+				match next(3) {
+					0 => maxsat.add_hard(&clause).unwrap(),
+					_ => maxsat.add_soft(&clause, 1 + next(5)).unwrap(),
+				}
+			}
+			let best = (0..1u32 << n)
+				.map(|bits| (0..n).map(|i| bits >> i & 1 == 1).collect::<Vec<_>>())
+				.filter(|model| {
+					maxsat.hard.iter().all(|clause| {
+						clause
+							.iter()
+							.any(|&lit| model[lit.unsigned_abs() as usize - 1] == (lit > 0))
+					})
+				})
+				.map(|model| maxsat.violated(&model))
+				.min();
+			let result = maxsat.solve_maxsat();
+			assert_eq!(result.as_ref().map(|r| r.0), best);
+			if let Some((cost, model)) = result {
+				assert_eq!(maxsat.violated(&model), cost);
+			}
+		}
+	}
+}
@@ -0,0 +1,199 @@
+//! A simple DPLL solver used as a reference for the fast one.
+//!
+//! It works on plain vectors of literals, without watched literals or any
+//! unsafe code, so that its verdict can be trusted when it is compared with
+//! [`Solver::solve`] in tests.
+
+use crate::{LitInt, Solver};
+
+/// Decides whether the clauses are satisfiable, by unit propagation and
+/// splitting on the first literal of the first clause.
+fn dpll(mut clauses: Vec<Vec<LitInt>>) -> bool {
+	// Propagate unit clauses until none is left.
+	while let Some(unit) = clauses.iter().find(|c| c.len() == 1) {
+		clauses = simplify(&clauses, unit[0]);
+	}
+	match clauses.first() {
+		None => true,
+		Some(clause) if clause.is_empty() => false,
+		Some(clause) => {
+			let lit = clause[0];
+			dpll(simplify(&clauses, lit)) || dpll(simplify(&clauses, -lit))
+		}
+	}
+}
+
+/// Whether unit propagation on the clauses with the negation of `clause`
+/// derives the empty clause, so that `clause` is implied by them.
+pub(crate) fn implied_by_propagation(mut clauses: Vec<Vec<LitInt>>, clause: &[LitInt]) -> bool {
+	for &lit in clause {
+		clauses = simplify(&clauses, -lit);
+	}
+	loop {
+		if clauses.first().is_some_and(|c| c.is_empty()) {
+			return true;
+		}
+		match clauses.iter().find(|c| c.len() == 1) {
+			Some(unit) => clauses = simplify(&clauses, unit[0]),
+			None => return false,
+		}
+	}
+}
+
+/// Assigns `lit` to be true; removes the satisfied clauses and `-lit` from
+/// the others. An empty clause, if any, is moved to the front.
+fn simplify(clauses: &[Vec<LitInt>], lit: LitInt) -> Vec<Vec<LitInt>> {
+	let mut result: Vec<Vec<LitInt>> = clauses
+		.iter()
+		.filter(|c| !c.contains(&lit))
+		.map(|c| c.iter().copied().filter(|&l| l != -lit).collect())
+		.collect();
+	if let Some(i) = result.iter().position(|c| c.is_empty()) {
+		result.swap(0, i);
+	}
+	result
+}
+
+impl Solver {
+	/// Decides the satisfiability of the original clauses of
+	/// [`Solver::to_clauses`] by a naive DPLL, without touching the state of
+	/// the solver.
+	///
+	/// It is much slower than [`Solver::solve`], and exists to check it.
+	/// Learned clauses and assumptions are not used.
+	pub fn solve_naive(&self) -> bool {
+		let mut clauses: Vec<Vec<LitInt>> = self
+			.original_clauses()
+			.map(|c| c.iter().map(|lit| lit.get()).collect())
+			.collect();
+		if self.empty {
+			clauses.insert(0, Vec::new());
+		}
+		dpll(clauses)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::{lcg, random_cnf};
+
+	#[test]
+	fn dpll_test() {
+		assert!(dpll(vec![]));
+		assert!(!dpll(vec![vec![]]));
+		assert!(!dpll(vec![vec![1], vec![-1]]));
+		assert!(dpll(vec![vec![1, 2], vec![-1, 2], vec![1, -2]]));
+		assert!(!dpll(vec![
+			vec![1, 2],
+			vec![-1, 2],
+			vec![1, -2],
+			vec![-1, -2]
+		]));
+	}
+
+	#[test]
+	fn implied_by_propagation_test() {
+		let clauses = vec![vec![1, 2], vec![-2, 3], vec![-1, 3]];
+		assert!(implied_by_propagation(clauses.clone(), &[3]));
+		assert!(implied_by_propagation(clauses.clone(), &[1, 3]));
+		assert!(!implied_by_propagation(clauses.clone(), &[1]));
+		assert!(!implied_by_propagation(clauses, &[]));
+		assert!(implied_by_propagation(vec![vec![1], vec![-1]], &[]));
+	}
+
+	#[test]
+	fn differential_test() {
+		// Random 3-SAT around the threshold.
+		let mut next = lcg(12345);
+		for _ in 0..200 {
+			let n = 1 + next(12) as usize;
+			let m = next(6 * n as u64) as usize;
+			let clauses = random_cnf(next(1 << 32), n, m, false);
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			let expected = ss.solve_naive();
+			assert_eq!(ss.solve().is_sat(), expected, "{:?}", clauses);
+			assert_eq!(ss.solve_naive(), expected);
+		}
+	}
+
+	/// Like `differential_test`, but on many more formulas with a rare empty
+	/// clause, each solved several times under random settings and
+	/// assumptions while clauses are added. Run it by
+	/// `cargo test --release -- --ignored fuzz_test`, with the environment
+	/// variables `FUZZ_SEED` and `FUZZ_ITERATIONS` to change the seed `1` and
+	/// the number of formulas `100000`.
+	#[test]
+	#[ignore]
+	fn fuzz_test() {
+		use crate::{Minimization, PhaseSaving, ReduceStrategy, RestartStrategy};
+		let env = |name: &str, default: u64| {
+			std::env::var(name).map_or(default, |value| value.parse().unwrap())
+		};
+		let mut next = lcg(env("FUZZ_SEED", 1));
+		// A random clause of 3 literals, which may repeat, or rarely the empty
+		// clause.
+		let clause = |next: &mut dyn FnMut(u64) -> u64, n| match next(120) {
+			0 => Vec::new(),
+			_ => random_cnf(next(1 << 32), n, 1, false).remove(0),
+		};
+		for iteration in 0..env("FUZZ_ITERATIONS", 100_000) {
+			let n = 1 + next(20) as usize;
+			let mut clauses: Vec<_> = (0..1 + next(4 * n as u64))
+				.map(|_| clause(&mut next, n))
+				.collect();
+			let mut ss = Solver::new(n, &clauses).unwrap();
+			ss.set_ccmin_mode(
+				[Minimization::None, Minimization::Basic, Minimization::Deep][next(3) as usize],
+			);
+			ss.set_phase_saving(
+				[PhaseSaving::None, PhaseSaving::Limited, PhaseSaving::Full][next(3) as usize],
+			);
+			ss.set_restart_strategy(
+				[
+					RestartStrategy::Luby,
+					RestartStrategy::Glucose,
+					RestartStrategy::None,
+				][next(3) as usize],
+			);
+			ss.set_reduce_strategy(
+				[ReduceStrategy::Lbd, ReduceStrategy::Activity][next(2) as usize],
+			);
+			ss.set_restart_base(1 + next(20) as usize);
+			ss.set_chronological(next(2) == 0);
+			ss.set_inprocessing(next(2) == 0);
+			ss.set_rephase(next(2) == 0);
+			ss.set_random_var_freq([0.0, 0.1][next(2) as usize]);
+			ss.set_seed(next(1 << 32));
+			// Reductions on the small formulas too.
+			ss.next_reduce = 1 + next(50);
+			for _ in 0..1 + next(4) {
+				let mut assumptions = random_cnf(next(1 << 32), n, 1, false).remove(0);
+				assumptions.truncate(next(4) as usize);
+				let result = ss.solve_with_assumptions(&assumptions);
+				let mut expected = clauses.clone();
+				expected.extend(assumptions.iter().map(|&lit| vec![lit]));
+				let expected = dpll(expected);
+				let context = (iteration, &clauses, &assumptions);
+				assert_eq!(result.is_sat(), expected, "{:?}", context);
+				if expected {
+					assert!(ss.check_model(), "{:?}", context);
+					assert!(assumptions
+						.iter()
+						.all(|&lit| ss.model_value(lit) == Some(true)));
+				} else {
+					// The failed assumptions are enough for the conflict.
+					let failed = ss.failed_assumptions();
+					assert!(failed.iter().all(|lit| assumptions.contains(lit)));
+					let mut core = clauses.clone();
+					core.extend(failed.iter().map(|&lit| vec![lit]));
+					assert!(!dpll(core), "{:?}", context);
+				}
+				let added = clause(&mut next, n);
+				ss.add_clause(&added).unwrap();
+				clauses.push(added);
+			}
+			assert_eq!(ss.solve_naive(), dpll(clauses));
+		}
+	}
+}
@@ -0,0 +1,87 @@
+//! Parallel solving by a portfolio of differently configured solvers.
+
+use crate::{normalize_clause, BuildError, LitInt, SolveResult, Solver};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread;
+
+/// The activity decay, the restart base and the initial phase of each
+/// member of the portfolio. A member beyond them reuses them with the
+/// opposite phase and random decisions seeded by its index.
+const CONFIGS: [(f64, usize, bool); 4] = [
+	(0.95, 100, false),
+	(0.9, 50, true),
+	(0.99, 300, false),
+	(0.85, 1000, true),
+];
+
+/// Solves the formula with `threads` solvers in parallel, at least one,
+/// each with its own configuration, and returns the result of the first one
+/// to decide as soon as it does.
+///
+/// The other threads are left to stop in the background through their
+/// terminate callbacks, which are polled every 1024 conflicts. The clauses are
+/// copied once and shared by the threads. The model is not returned, since
+/// it lives in the winning solver.
+///
+/// # Panics
+///
+/// Panics if some clause has a literal which is zero or out of `-n..=n`.
+pub fn solve_portfolio(clauses: &[Vec<LitInt>], n: usize, threads: usize) -> SolveResult {
+	for (i, clause) in clauses.iter().enumerate() {
+		if let Err(error) = normalize_clause(n, clause) {
+			panic!("{}", BuildError { clause: i, error });
+		}
+	}
+	let clauses = Arc::new(clauses.to_vec());
+	let stop = Arc::new(AtomicBool::new(false));
+	let (tx, rx) = mpsc::channel();
+	for i in 0..threads.max(1) {
+		let (clauses, stop, tx) = (clauses.clone(), stop.clone(), tx.clone());
+		thread::spawn(move || {
+			// The clauses are checked above.
+			let mut solver = unsafe { Solver::new_unchecked(n, &clauses) };
+			drop(clauses);
+			let (decay, base, phase) = CONFIGS[i % CONFIGS.len()];
+			solver.set_var_decay(decay);
+			solver.set_restart_base(base);
+			solver.suggest.fill(phase ^ (i / CONFIGS.len() % 2 == 1));
+			if i >= CONFIGS.len() {
+				solver.set_random_var_freq(0.02);
+				solver.set_seed(i as u64);
+			}
+			solver.set_terminate_callback(move || stop.load(Ordering::Relaxed));
+			// The receiver is gone if another thread has decided.
+			let _ = tx.send(solver.solve());
+		});
+	}
+	drop(tx);
+	let result = rx
+		.iter()
+		.find(|&result| result != SolveResult::Unknown)
+		.unwrap_or(SolveResult::Unknown);
+	stop.store(true, Ordering::Relaxed);
+	result
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::tests::pigeonhole;
+
+	#[test]
+	fn portfolio_test() {
+		let (n, clauses) = pigeonhole(7, 6);
+		assert_eq!(solve_portfolio(&clauses, n, 4), SolveResult::Unsat);
+		let (n, clauses) = pigeonhole(6, 6);
+		for threads in [0, 1, 9] {
+			assert_eq!(solve_portfolio(&clauses, n, threads), SolveResult::Sat);
+		}
+	}
+
+	#[test]
+	#[should_panic(expected = "clause 1: ")]
+	fn portfolio_bad_clause_test() {
+		solve_portfolio(&[vec![1], vec![1, 3]], 2, 2);
+	}
+}
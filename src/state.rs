@@ -0,0 +1,209 @@
+//! Serialization of the solver state, enabled by the `serde` feature.
+//!
+//! Clause pointers cannot be serialized, so the solver is written as a plain
+//! `SolverState` with the literals of the clauses, and a reason is written
+//! as the index of the clause. The literals are kept in the internal order,
+//! so that the watched literals stay on the ends when the watchers are
+//! rebuilt. The heuristics (activities, saved phases), the counters and the
+//! settings are not saved.
+
+use crate::{check_clause, ClauseBody, ClausePtr, Learned, Lit, LitFunctions, LitInt, Solver};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::HashMap;
+
+#[derive(Serialize, Deserialize)]
+struct SolverState {
+	n: usize,
+	/// The original clauses without the empty clause, after the
+	/// preprocessing.
+	clauses: Vec<Vec<LitInt>>,
+	/// The original clauses before the preprocessing, if they are kept.
+	original: Option<Vec<Vec<LitInt>>>,
+	/// Whether the empty clause is given.
+	empty: bool,
+	/// The literals of the unit clauses, including the pure literals.
+	units: Vec<LitInt>,
+	/// The learned clauses with their LBD.
+	learned: Vec<(Vec<LitInt>, usize)>,
+	/// The removed clauses with their blocking literals.
+	eliminated: Vec<(LitInt, Vec<LitInt>)>,
+	unsat: bool,
+	/// The assigned literals in the assignment order, each with its level and
+	/// the index of its reason in `clauses` followed by `learned`.
+	trail: Vec<(LitInt, usize, Option<usize>)>,
+	level: usize,
+	status: Option<bool>,
+}
+
+fn ints(lits: &[Lit]) -> Vec<LitInt> {
+	lits.iter().map(|lit| lit.get()).collect()
+}
+
+impl Serialize for Solver {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		let index: HashMap<ClausePtr, usize> = self
+			.clauses
+			.iter()
+			.chain(self.learned.iter().map(|c| &c.ptr))
+			.enumerate()
+			.map(|(i, &ptr)| (ptr, i))
+			.collect();
+		SolverState {
+			n: self.n,
+			clauses: self.clauses().map(ints).collect(),
+			original: self
+				.original
+				.as_ref()
+				.map(|original| original.iter().map(|clause| ints(clause)).collect()),
+			empty: self.empty,
+			units: ints(&self.units),
+			learned: self
+				.learned
+				.iter()
+				.map(|c| (ints(Self::lits(c.ptr)), c.lbd))
+				.collect(),
+			eliminated: self
+				.eliminated
+				.iter()
+				.map(|(lit, clause)| (lit.get(), ints(clause)))
+				.collect(),
+			unsat: self.unsat,
+			trail: self
+				.trail
+				.iter()
+				.map(|lit| {
+					let var = lit.var();
+					let reason = self.reasons[var].map(|ptr| index[&ptr]);
+					(lit.get(), self.levels[var], reason)
+				})
+				.collect(),
+			level: self.level,
+			status: self.status,
+		}
+		.serialize(serializer)
+	}
+}
+
+impl<'de> Deserialize<'de> for Solver {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		let state = SolverState::deserialize(deserializer)?;
+		let n = state.n;
+		let lit = |lit: LitInt| match Lit::new(lit) {
+			Some(lit) if lit.var() <= n => Ok(lit),
+			_ => Err(D::Error::custom(format!("bad literal {}", lit))),
+		};
+		for clause in state
+			.clauses
+			.iter()
+			.chain(state.learned.iter().map(|c| &c.0))
+			.chain(state.original.iter().flatten())
+		{
+			match check_clause(n, clause) {
+				Ok(false) if !clause.is_empty() => {}
+				_ => return Err(D::Error::custom("bad clause")),
+			}
+		}
+		let clauses: Vec<_> = state.clauses.iter().map(|c| &c[..]).collect();
+		// The clauses are checked above.
+		let mut solver = unsafe { Solver::build(n, &clauses) };
+		solver.empty = state.empty;
+		solver.unsat = state.unsat;
+		if let Some(original) = state.original {
+			let original = original.into_iter().map(|clause| clause.into_iter().map(lit).collect());
+			solver.original = Some(original.collect::<Result<_, _>>()?);
+		}
+		solver.units = state.units.into_iter().map(lit).collect::<Result<_, _>>()?;
+		for (l, clause) in state.eliminated {
+			let clause: Vec<_> = clause.into_iter().map(lit).collect::<Result<_, _>>()?;
+			solver.eliminated.push((lit(l)?, clause));
+		}
+		for (lits, lbd) in state.learned {
+			let lits: Vec<_> = lits.into_iter().map(lit).collect::<Result<_, _>>()?;
+			let body = ClauseBody::new(lits.into_boxed_slice());
+			let ptr = body.ptr();
+			solver.learned.push(Learned { body, ptr, lbd });
+		}
+		solver.rebuild_watchers();
+		let ptrs: Vec<ClausePtr> = solver
+			.clauses
+			.iter()
+			.chain(solver.learned.iter().map(|c| &c.ptr))
+			.copied()
+			.collect();
+		for (l, level, reason) in state.trail {
+			let l = lit(l)?;
+			if solver.assign[l.var()].is_some() || level > state.level {
+				return Err(D::Error::custom("bad trail"));
+			}
+			let reason = match reason {
+				Some(i) => Some(*ptrs.get(i).ok_or_else(|| D::Error::custom("bad reason"))?),
+				None => None,
+			};
+			solver.level = level;
+			solver.set(l, reason);
+		}
+		solver.qhead = solver.trail.len();
+		solver.out_of_order = solver
+			.trail
+			.windows(2)
+			.any(|w| solver.levels[w[0].var()] > solver.levels[w[1].var()]);
+		solver.level = state.level;
+		solver.status = state.status;
+		Ok(solver)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn serde_test() {
+		let clauses = vec![
+			vec![1, 2, 3],
+			vec![-1, -2],
+			vec![-2, -3],
+			vec![-1, -3],
+			vec![4],
+		];
+		let mut ss = Solver::new(4, &clauses).unwrap();
+		assert!(ss.solve().is_sat());
+		let json = serde_json::to_string(&ss).unwrap();
+		let mut restored: Solver = serde_json::from_str(&json).unwrap();
+		assert_eq!(restored.to_string(), ss.to_string());
+		assert_eq!(restored.model(), ss.model());
+		assert_eq!(restored.trail, ss.trail);
+		assert_eq!(serde_json::to_string(&restored).unwrap(), json);
+		assert!(restored.solve().is_sat());
+		restored.add_clause(&[-4]).unwrap();
+		assert!(restored.solve().is_unsat());
+
+		// In the middle of a search, with a learned clause as a reason.
+		let lit = |l| Lit::new(l).unwrap();
+		let mut ss = Solver::new(3, &[vec![-1, 2, 3], vec![-1, 2, -3]]).unwrap();
+		ss.level = 1;
+		assert!(ss.assign_and_propagate(lit(1), None).is_none());
+		ss.level = 2;
+		let conflict = ss.assign_and_propagate(lit(-2), None).unwrap();
+		let (learned, level) = ss.analyze(conflict);
+		let ptr = ss.learn(learned);
+		ss.backjump(level);
+		assert!(ss.assign_and_propagate(lit(2), Some(ptr)).is_none());
+		let json = serde_json::to_string(&ss).unwrap();
+		let mut restored: Solver = serde_json::from_str(&json).unwrap();
+		assert_eq!(restored.num_learned(), 1);
+		assert_eq!(restored.reasons[2], Some(restored.learned[0].ptr));
+		assert_eq!(restored.level, 1);
+		assert!(restored.solve().is_sat());
+
+		assert!(serde_json::from_str::<Solver>(&json.replace("\"n\":3", "\"n\":2")).is_err());
+
+		// The original clauses kept by the preprocessing.
+		let mut ss = Solver::new(2, &[vec![1, 2], vec![1, -2], vec![-1, 2]]).unwrap();
+		ss.eliminate_vars(0);
+		let restored: Solver = serde_json::from_str(&serde_json::to_string(&ss).unwrap()).unwrap();
+		assert_eq!(restored.to_clauses(), ss.to_clauses());
+		assert_eq!(restored.clauses().count(), ss.clauses().count());
+	}
+}